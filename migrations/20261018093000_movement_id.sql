@@ -0,0 +1,3 @@
+-- give every accounting movement an identity, existing rows get a fresh uuid
+ALTER TABLE accounting_movement_tbl
+    ADD COLUMN id UUID DEFAULT gen_random_uuid() PRIMARY KEY;
@@ -0,0 +1,15 @@
+-- a user can keep several accounting books, one of them is the current one
+CREATE UNIQUE INDEX tbl_accounting_user_id_name_idx ON tbl_accounting(user_id, name);
+
+ALTER TABLE tbl_user
+    ADD COLUMN current_accounting_id UUID,
+    ADD FOREIGN KEY(current_accounting_id) REFERENCES tbl_accounting(id) ON DELETE SET NULL;
+
+UPDATE tbl_user tu
+SET current_accounting_id = (
+    SELECT ta.id
+    FROM tbl_accounting ta
+    WHERE ta.user_id = tu.id
+    ORDER BY ta.name = 'default' DESC, ta.created_at
+    LIMIT 1
+);
@@ -0,0 +1,4 @@
+-- archived types are kept for the tickets that already use them but can't be picked anymore
+ALTER TABLE tbl_type ADD COLUMN archived_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX tbl_type_user_id_idx ON tbl_type(user_id);
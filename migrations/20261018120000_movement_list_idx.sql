@@ -0,0 +1,5 @@
+-- support the sorted and paginated ticket list
+CREATE INDEX accounting_movement_tbl_accounting_id_created_at_idx
+    ON accounting_movement_tbl(accounting_id, created_at, id);
+CREATE INDEX accounting_movement_tbl_accounting_id_amount_idx
+    ON accounting_movement_tbl(accounting_id, amount, id);
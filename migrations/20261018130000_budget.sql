@@ -0,0 +1,23 @@
+CREATE TYPE BUDGET_PERIOD AS ENUM ('month', 'year');
+
+CREATE TABLE tbl_budget (
+    id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+    user_id UUID NOT NULL,
+    type_id UUID NOT NULL,
+    -- NULL when the budget covers every book of the user
+    accounting_id UUID,
+    period BUDGET_PERIOD NOT NULL,
+    amount NUMERIC(14,2) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    FOREIGN KEY(user_id) REFERENCES tbl_user(id),
+    FOREIGN KEY(type_id) REFERENCES tbl_type(id),
+    FOREIGN KEY(accounting_id) REFERENCES tbl_accounting(id) ON DELETE CASCADE
+);
+
+-- one budget per category, book and period
+CREATE UNIQUE INDEX tbl_budget_scope_idx ON tbl_budget(
+    user_id,
+    type_id,
+    COALESCE(accounting_id, '00000000-0000-0000-0000-000000000000'),
+    period
+);
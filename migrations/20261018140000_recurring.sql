@@ -0,0 +1,32 @@
+CREATE TYPE RECURRENCE_FREQUENCY AS ENUM ('daily', 'weekly', 'monthly', 'yearly');
+
+CREATE TABLE tbl_recurring (
+    id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+    accounting_id UUID NOT NULL,
+    type_id UUID NOT NULL,
+    direction MOVEMENT_DIRECTION NOT NULL,
+    amount NUMERIC(14,2) NOT NULL,
+    description TEXT NOT NULL,
+    frequency RECURRENCE_FREQUENCY NOT NULL,
+    -- monthly rules only, clamped to the last day of shorter months
+    day_of_month SMALLINT CHECK (day_of_month BETWEEN 1 AND 31),
+    starts_on DATE NOT NULL,
+    ends_on DATE,
+    max_occurrences INTEGER CHECK (max_occurrences > 0),
+    occurrences INTEGER NOT NULL DEFAULT 0,
+    last_occurrence DATE,
+    -- NULL once the rule is over
+    next_due DATE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    FOREIGN KEY(accounting_id) REFERENCES tbl_accounting(id) ON DELETE CASCADE,
+    FOREIGN KEY(type_id) REFERENCES tbl_type(id)
+);
+
+CREATE INDEX tbl_recurring_next_due_idx ON tbl_recurring(next_due);
+
+ALTER TABLE accounting_movement_tbl
+    ADD COLUMN recurring_id UUID REFERENCES tbl_recurring(id) ON DELETE SET NULL;
+
+-- a rule creates at most one movement per occurrence
+CREATE UNIQUE INDEX accounting_movement_tbl_recurring_idx
+    ON accounting_movement_tbl(recurring_id, created_at);
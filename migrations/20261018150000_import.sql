@@ -0,0 +1,31 @@
+-- identifier of an imported movement in its source (bank transaction id, row fingerprint...)
+ALTER TABLE accounting_movement_tbl ADD COLUMN external_id TEXT;
+CREATE UNIQUE INDEX accounting_movement_tbl_external_id_idx
+    ON accounting_movement_tbl(accounting_id, external_id);
+
+-- parsed statements waiting for the user to confirm them
+CREATE TABLE tbl_import (
+    id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+    user_id UUID NOT NULL,
+    accounting_id UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    FOREIGN KEY(user_id) REFERENCES tbl_user(id) ON DELETE CASCADE,
+    FOREIGN KEY(accounting_id) REFERENCES tbl_accounting(id) ON DELETE CASCADE
+);
+
+CREATE TABLE tbl_import_row (
+    import_id UUID NOT NULL,
+    line INTEGER NOT NULL,
+    -- every other column is NULL when the row couldn't be read
+    error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE,
+    direction MOVEMENT_DIRECTION,
+    amount NUMERIC(14,2),
+    description TEXT,
+    type_id UUID,
+    external_id TEXT,
+    duplicate BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY(import_id, line),
+    FOREIGN KEY(import_id) REFERENCES tbl_import(id) ON DELETE CASCADE,
+    FOREIGN KEY(type_id) REFERENCES tbl_type(id)
+);
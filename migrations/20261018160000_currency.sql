@@ -0,0 +1,49 @@
+-- ISO 4217 currencies and the number of decimals their amounts carry
+CREATE TABLE tbl_currency (
+    code CHAR(3) PRIMARY KEY,
+    minor_units SMALLINT NOT NULL CHECK (minor_units BETWEEN 0 AND 3)
+);
+
+INSERT INTO tbl_currency(code, minor_units) VALUES
+    ('AED', 2), ('ARS', 2), ('AUD', 2), ('BGN', 2), ('BHD', 3), ('BIF', 0), ('BRL', 2),
+    ('CAD', 2), ('CHF', 2), ('CLP', 0), ('CNY', 2), ('CZK', 2), ('DJF', 0), ('DKK', 2),
+    ('EGP', 2), ('EUR', 2), ('GBP', 2), ('GNF', 0), ('HKD', 2), ('HUF', 2), ('IDR', 2),
+    ('ILS', 2), ('INR', 2), ('IQD', 3), ('ISK', 0), ('JOD', 3), ('JPY', 0), ('KMF', 0),
+    ('KRW', 0), ('KWD', 3), ('LYD', 3), ('MAD', 2), ('MXN', 2), ('MYR', 2), ('NOK', 2),
+    ('NZD', 2), ('OMR', 3), ('PHP', 2), ('PLN', 2), ('PYG', 0), ('RON', 2), ('RSD', 2),
+    ('RWF', 0), ('SAR', 2), ('SEK', 2), ('SGD', 2), ('THB', 2), ('TND', 3), ('TRY', 2),
+    ('TWD', 2), ('UAH', 2), ('UGX', 0), ('USD', 2), ('VND', 0), ('VUV', 0), ('XAF', 0),
+    ('XOF', 0), ('XPF', 0), ('ZAR', 2);
+
+-- base currency of a book, reports are converted to it
+ALTER TABLE tbl_accounting
+    ADD COLUMN currency CHAR(3) NOT NULL DEFAULT 'EUR' REFERENCES tbl_currency(code);
+
+-- amounts keep up to 3 decimals, the most any currency has, instead of a fixed 2. Shown amounts
+-- are rounded back to the decimals of their currency.
+ALTER TABLE accounting_movement_tbl ALTER COLUMN amount TYPE NUMERIC(15,3);
+ALTER TABLE accounting_movement_tbl ADD COLUMN currency CHAR(3) REFERENCES tbl_currency(code);
+UPDATE accounting_movement_tbl SET currency = 'EUR';
+ALTER TABLE accounting_movement_tbl ALTER COLUMN currency SET NOT NULL;
+
+ALTER TABLE tbl_recurring ALTER COLUMN amount TYPE NUMERIC(15,3);
+ALTER TABLE tbl_recurring ADD COLUMN currency CHAR(3) REFERENCES tbl_currency(code);
+UPDATE tbl_recurring SET currency = 'EUR';
+ALTER TABLE tbl_recurring ALTER COLUMN currency SET NOT NULL;
+
+ALTER TABLE tbl_import_row ALTER COLUMN amount TYPE NUMERIC(15,3);
+ALTER TABLE tbl_import_row ADD COLUMN currency CHAR(3);
+
+-- 1 `base` is worth `rate` `quote` from `day` until the next rate of the pair
+CREATE TABLE tbl_exchange_rate (
+    user_id UUID NOT NULL,
+    base CHAR(3) NOT NULL,
+    quote CHAR(3) NOT NULL,
+    day DATE NOT NULL,
+    rate NUMERIC NOT NULL CHECK (rate > 0),
+    PRIMARY KEY(user_id, base, quote, day),
+    FOREIGN KEY(user_id) REFERENCES tbl_user(id) ON DELETE CASCADE,
+    FOREIGN KEY(base) REFERENCES tbl_currency(code),
+    FOREIGN KEY(quote) REFERENCES tbl_currency(code),
+    CHECK (base <> quote)
+);
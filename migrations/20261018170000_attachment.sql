@@ -0,0 +1,12 @@
+-- receipts and other files of a ticket, their content is in the blob store under `storage_key`
+CREATE TABLE tbl_attachment (
+    id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+    movement_id UUID NOT NULL,
+    file_name TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    size BIGINT NOT NULL,
+    storage_key TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    FOREIGN KEY(movement_id) REFERENCES accounting_movement_tbl(id) ON DELETE CASCADE
+);
+CREATE INDEX tbl_attachment_movement_id_idx ON tbl_attachment(movement_id);
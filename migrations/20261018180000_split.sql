@@ -0,0 +1,26 @@
+-- lines a ticket is split into, each with its own category, they add up to the ticket amount
+CREATE TABLE tbl_split (
+    id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+    movement_id UUID NOT NULL,
+    position INT NOT NULL,
+    type_id UUID NOT NULL,
+    amount NUMERIC(15,3) NOT NULL CHECK (amount > 0),
+    note TEXT NOT NULL DEFAULT '',
+    UNIQUE(movement_id, position),
+    FOREIGN KEY(movement_id) REFERENCES accounting_movement_tbl(id) ON DELETE CASCADE,
+    FOREIGN KEY(type_id) REFERENCES tbl_type(id)
+);
+
+-- what reports count: a row per line of a split ticket, the ticket itself otherwise
+CREATE VIEW movement_line AS
+    SELECT
+        amt.id,
+        amt.accounting_id,
+        amt.direction,
+        amt.created_at,
+        amt.currency,
+        amt.description,
+        COALESCE(ts.type_id, amt.type_id) as type_id,
+        COALESCE(ts.amount, amt.amount) as amount
+    FROM accounting_movement_tbl amt
+      LEFT JOIN tbl_split ts ON ts.movement_id = amt.id;
@@ -0,0 +1,18 @@
+-- free-form labels of a user, across categories, names are stored lower case
+CREATE TABLE tbl_tag (
+    id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+    user_id UUID NOT NULL,
+    name TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE(user_id, name),
+    FOREIGN KEY(user_id) REFERENCES tbl_user(id) ON DELETE CASCADE
+);
+
+CREATE TABLE tbl_movement_tag (
+    movement_id UUID NOT NULL,
+    tag_id UUID NOT NULL,
+    PRIMARY KEY(movement_id, tag_id),
+    FOREIGN KEY(movement_id) REFERENCES accounting_movement_tbl(id) ON DELETE CASCADE,
+    FOREIGN KEY(tag_id) REFERENCES tbl_tag(id) ON DELETE CASCADE
+);
+CREATE INDEX tbl_movement_tag_tag_id_idx ON tbl_movement_tag(tag_id);
@@ -0,0 +1,22 @@
+-- the category both sides of a transfer between books are filed under
+INSERT INTO tbl_type(id,parent_id,name,user_id)
+VALUES ('0b6c5f0e-2d4a-4f3b-9c1e-7a8d6e5f4c3b',NULL,'transfer',NULL);
+
+-- shared by the `out` and `in` movements of a transfer between two books
+ALTER TABLE accounting_movement_tbl ADD COLUMN transfer_id UUID;
+CREATE INDEX accounting_movement_tbl_transfer_id_idx
+    ON accounting_movement_tbl(transfer_id) WHERE transfer_id IS NOT NULL;
+
+CREATE OR REPLACE VIEW movement_line AS
+    SELECT
+        amt.id,
+        amt.accounting_id,
+        amt.direction,
+        amt.created_at,
+        amt.currency,
+        amt.description,
+        COALESCE(ts.type_id, amt.type_id) as type_id,
+        COALESCE(ts.amount, amt.amount) as amount,
+        amt.transfer_id
+    FROM accounting_movement_tbl amt
+      LEFT JOIN tbl_split ts ON ts.movement_id = amt.id;
@@ -0,0 +1,48 @@
+-- what a member can do in a book: owners also manage the book and its members, editors write
+-- tickets and viewers only read them
+CREATE TYPE BOOK_ROLE AS ENUM ('owner', 'editor', 'viewer');
+
+-- `tbl_accounting.user_id` stays the user who created the book, access goes through here
+CREATE TABLE tbl_accounting_member (
+    accounting_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    role BOOK_ROLE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY(accounting_id, user_id),
+    FOREIGN KEY(accounting_id) REFERENCES tbl_accounting(id) ON DELETE CASCADE,
+    FOREIGN KEY(user_id) REFERENCES tbl_user(id) ON DELETE CASCADE
+);
+CREATE INDEX tbl_accounting_member_user_id_idx ON tbl_accounting_member(user_id);
+
+INSERT INTO tbl_accounting_member(accounting_id, user_id, role)
+SELECT id, user_id, 'owner' FROM tbl_accounting;
+
+-- invitations waiting for the invited user to accept or decline them
+CREATE TABLE tbl_invitation (
+    id UUID DEFAULT gen_random_uuid() PRIMARY KEY,
+    accounting_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    role BOOK_ROLE NOT NULL,
+    invited_by UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE(accounting_id, user_id),
+    FOREIGN KEY(accounting_id) REFERENCES tbl_accounting(id) ON DELETE CASCADE,
+    FOREIGN KEY(user_id) REFERENCES tbl_user(id) ON DELETE CASCADE,
+    FOREIGN KEY(invited_by) REFERENCES tbl_user(id) ON DELETE CASCADE
+);
+CREATE INDEX tbl_invitation_user_id_idx ON tbl_invitation(user_id);
+
+-- the member who wrote a movement or a recurring rule, NULL once their account is gone
+ALTER TABLE accounting_movement_tbl
+    ADD COLUMN created_by UUID REFERENCES tbl_user(id) ON DELETE SET NULL;
+UPDATE accounting_movement_tbl amt
+SET created_by = ta.user_id
+FROM tbl_accounting ta
+WHERE ta.id = amt.accounting_id;
+
+ALTER TABLE tbl_recurring
+    ADD COLUMN created_by UUID REFERENCES tbl_user(id) ON DELETE SET NULL;
+UPDATE tbl_recurring tr
+SET created_by = ta.user_id
+FROM tbl_accounting ta
+WHERE ta.id = tr.accounting_id;
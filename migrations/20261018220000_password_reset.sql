@@ -0,0 +1,10 @@
+-- single-use password reset links, only the sha256 of the token is kept
+CREATE TABLE tbl_password_reset (
+    token_hash TEXT PRIMARY KEY,
+    user_id UUID NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY(user_id) REFERENCES tbl_user(id) ON DELETE CASCADE
+);
+
+CREATE INDEX tbl_password_reset_user_id ON tbl_password_reset(user_id);
@@ -0,0 +1,3 @@
+-- users confirm their email through a signed link, the ones from before are trusted
+ALTER TABLE tbl_user ADD COLUMN email_verified_at TIMESTAMPTZ;
+UPDATE tbl_user SET email_verified_at = COALESCE(created_at, NOW());
@@ -0,0 +1,20 @@
+-- optional TOTP second factor, it is on once `confirmed_at` is set
+CREATE TABLE tbl_totp (
+    user_id UUID PRIMARY KEY,
+    -- base32 shared secret
+    secret TEXT NOT NULL,
+    confirmed_at TIMESTAMPTZ,
+    -- last time step used to log in, a code can't be used twice
+    last_step BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY(user_id) REFERENCES tbl_user(id) ON DELETE CASCADE
+);
+
+-- one-time codes to log in without the authenticator, only their sha256 is kept
+CREATE TABLE tbl_recovery_code (
+    user_id UUID NOT NULL,
+    code_hash TEXT NOT NULL,
+    used_at TIMESTAMPTZ,
+    PRIMARY KEY(user_id, code_hash),
+    FOREIGN KEY(user_id) REFERENCES tbl_user(id) ON DELETE CASCADE
+);
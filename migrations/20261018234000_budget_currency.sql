@@ -0,0 +1,22 @@
+-- a budget is set in one currency, what is spent in others is converted to it. Its amount keeps
+-- up to 3 decimals like the amounts of tickets.
+ALTER TABLE tbl_budget ALTER COLUMN amount TYPE NUMERIC(15,3);
+ALTER TABLE tbl_budget ADD COLUMN currency CHAR(3) REFERENCES tbl_currency(code);
+UPDATE tbl_budget b SET currency = COALESCE(
+    (SELECT ta.currency FROM tbl_accounting ta WHERE ta.id = b.accounting_id),
+    (
+        SELECT ta.currency
+        FROM tbl_user tu INNER JOIN tbl_accounting ta ON ta.id = tu.current_accounting_id
+        WHERE tu.id = b.user_id
+    ),
+    (
+        SELECT ta.currency
+        FROM tbl_accounting_member tam INNER JOIN tbl_accounting ta ON ta.id = tam.accounting_id
+        WHERE tam.user_id = b.user_id
+        ORDER BY tam.role = 'owner' DESC, tam.created_at
+        LIMIT 1
+    ),
+    -- only left for users who are no longer member of any book
+    'EUR'
+);
+ALTER TABLE tbl_budget ALTER COLUMN currency SET NOT NULL;
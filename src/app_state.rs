@@ -1,3 +1,5 @@
+use crate::configuration::{AttachmentSettings, EmailVerificationSettings, LoginThrottleSettings};
+use crate::{mail::SharedMailer, storage::SharedBlobStore};
 use bb8_redis::{
     bb8::{Pool, PooledConnection},
     RedisConnectionManager,
@@ -15,4 +17,9 @@ pub struct AppState {
     pub redis_pool: RedisPool,
     pub auth_secret: SecretString,
     pub base_url: String,
+    pub blob_store: SharedBlobStore,
+    pub attachments: AttachmentSettings,
+    pub mailer: SharedMailer,
+    pub email_verification: EmailVerificationSettings,
+    pub login_throttle: LoginThrottleSettings,
 }
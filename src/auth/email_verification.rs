@@ -0,0 +1,292 @@
+use crate::{
+    app_state::{AppState, SharedAppState},
+    auth::mw_auth::CtxResult,
+    configuration::UnverifiedAccess,
+    mail::Mail,
+};
+use anyhow::Context;
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header::RETRY_AFTER, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use bb8_redis::redis::{AsyncCommands, ExistenceCheck, SetExpiry, SetOptions};
+use hmac::{Hmac, Mac};
+use secrecy::{ExposeSecret, SecretString};
+use sha2::Sha256;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(thiserror::Error, Debug)]
+pub enum VerificationError {
+    #[error("email already confirmed")]
+    AlreadyVerified,
+    /// Seconds before another mail can be sent.
+    #[error("a confirmation mail was just sent")]
+    TooSoon(u64),
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl IntoResponse for VerificationError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::AlreadyVerified => (StatusCode::BAD_REQUEST, self.to_string()).into_response(),
+            Self::TooSoon(seconds) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(RETRY_AFTER, seconds.to_string())],
+                self.to_string(),
+            )
+                .into_response(),
+            Self::UnexpectedError(e) => {
+                tracing::error!("Failed email verification: {:?}", e);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        }
+    }
+}
+
+/// The link signs the email too, so it stops working if the address changes.
+fn mac(secret: &SecretString, user_id: Uuid, email: &str, expires_at: i64) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.expose_secret().as_bytes())
+        .expect("HMAC accepts keys of any size");
+    mac.update(format!("{user_id}:{email}:{expires_at}").as_bytes());
+    mac
+}
+
+/// A token `user_id.expires_at.signature` for the confirmation link.
+pub fn sign(secret: &SecretString, user_id: Uuid, email: &str, ttl_hours: i64) -> String {
+    let expires_at = (chrono::Utc::now() + chrono::Duration::hours(ttl_hours)).timestamp();
+    signed_token(secret, user_id, email, expires_at)
+}
+
+fn signed_token(secret: &SecretString, user_id: Uuid, email: &str, expires_at: i64) -> String {
+    let signature = mac(secret, user_id, email, expires_at)
+        .finalize()
+        .into_bytes();
+    format!("{user_id}.{expires_at}.{signature:x}")
+}
+
+/// The parts of a token, before its signature is checked.
+struct SignedToken {
+    user_id: Uuid,
+    expires_at: i64,
+    signature: Vec<u8>,
+}
+
+impl SignedToken {
+    fn parse(token: &str) -> Option<Self> {
+        let mut parts = token.splitn(3, '.');
+        let (user_id, expires_at, signature) = (parts.next()?, parts.next()?, parts.next()?);
+        let signature = (0..signature.len())
+            .step_by(2)
+            .map(|i| {
+                signature
+                    .get(i..i + 2)
+                    .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+            })
+            .collect::<Option<Vec<u8>>>()?;
+        Some(Self {
+            user_id: user_id.parse().ok()?,
+            expires_at: expires_at.parse().ok()?,
+            signature,
+        })
+    }
+
+    fn is_expired(&self, now: i64) -> bool {
+        self.expires_at < now
+    }
+
+    fn is_signed_for(&self, secret: &SecretString, email: &str) -> bool {
+        mac(secret, self.user_id, email, self.expires_at)
+            .verify_slice(&self.signature)
+            .is_ok()
+    }
+}
+
+/// Confirm the email of a signed token, returns whether the token was valid.
+pub async fn verify(
+    pool: &PgPool,
+    secret: &SecretString,
+    token: &str,
+) -> Result<bool, sqlx::Error> {
+    let Some(token) = SignedToken::parse(token) else {
+        return Ok(false);
+    };
+    if token.is_expired(chrono::Utc::now().timestamp()) {
+        return Ok(false);
+    }
+    let Some(email) =
+        sqlx::query_scalar!("SELECT email FROM tbl_user WHERE id = $1", token.user_id)
+            .fetch_optional(pool)
+            .await?
+    else {
+        return Ok(false);
+    };
+    let valid = token.is_signed_for(secret, &email);
+    if valid {
+        sqlx::query!(
+            "UPDATE tbl_user SET email_verified_at = COALESCE(email_verified_at, NOW()) WHERE id = $1",
+            token.user_id,
+        )
+        .execute(pool)
+        .await?;
+    }
+    Ok(valid)
+}
+
+pub async fn is_verified(pool: &PgPool, user_id: Uuid) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"SELECT email_verified_at IS NOT NULL as "verified!" FROM tbl_user WHERE id = $1"#,
+        user_id,
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(row.verified)
+}
+
+/// Mail a confirmation link to the user, at most once per `resend_interval`.
+pub async fn send_link(state: &AppState, user_id: Uuid) -> Result<(), VerificationError> {
+    let user = sqlx::query!(
+        r#"SELECT email, email_verified_at IS NOT NULL as "verified!" FROM tbl_user WHERE id = $1"#,
+        user_id,
+    )
+    .fetch_one(&state.db_pool)
+    .await
+    .context("Failed fetching user")?;
+    if user.verified {
+        return Err(VerificationError::AlreadyVerified);
+    }
+    let mut conn = state
+        .redis_pool
+        .get()
+        .await
+        .context("Failed getting redis connection")?;
+    let key = format!("verify_email_sent:{user_id}");
+    let opts = SetOptions::default()
+        .conditional_set(ExistenceCheck::NX)
+        .with_expiration(SetExpiry::EX(state.email_verification.resend_interval));
+    let set: Option<String> = conn
+        .set_options(&key, 1, opts)
+        .await
+        .context("Failed limiting confirmation mails")?;
+    if set.is_none() {
+        let ttl: i64 = conn.ttl(&key).await.context("Failed reading limit")?;
+        return Err(VerificationError::TooSoon(ttl.max(1) as u64));
+    }
+    let token = sign(
+        &state.auth_secret,
+        user_id,
+        &user.email,
+        state.email_verification.link_ttl_hours,
+    );
+    let mail = Mail {
+        to: user.email,
+        subject: "Confirm your TicketApp email".to_string(),
+        body: format!(
+            "Welcome to TicketApp!\n\nConfirm this is your email address within {} hours at:\n\
+            {}/verify_email?token={token}",
+            state.email_verification.link_ttl_hours,
+            state.base_url.trim_end_matches('/'),
+        ),
+    };
+    state.mailer.send(mail).await?;
+    Ok(())
+}
+
+/// Keep unverified users to what `unverified_access` allows them.
+pub async fn mw_require_verified(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let blocked = match state.email_verification.unverified_access {
+        UnverifiedAccess::Full => false,
+        UnverifiedAccess::ReadOnly => !matches!(*req.method(), Method::GET | Method::HEAD),
+        UnverifiedAccess::None => true,
+    };
+    if let (true, Ok(ctx)) = (blocked, &ctx_res) {
+        match is_verified(&state.db_pool, ctx.user_id()).await {
+            Ok(true) => {}
+            Ok(false) => {
+                return (StatusCode::FORBIDDEN, "confirm your email address first").into_response()
+            }
+            Err(e) => {
+                tracing::error!("Failed checking email verification: {}", e);
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        }
+    }
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMAIL: &str = "bob@example.com";
+
+    fn secret() -> SecretString {
+        SecretString::new("a secret of the tests".into())
+    }
+
+    /// Whether the token would confirm `email` at `now`, without the database.
+    fn accepts(token: &str, email: &str, now: i64) -> bool {
+        SignedToken::parse(token)
+            .is_some_and(|token| !token.is_expired(now) && token.is_signed_for(&secret(), email))
+    }
+
+    #[test]
+    fn signed_tokens_are_accepted_until_they_expire() {
+        let user_id = Uuid::new_v4();
+        let now = chrono::Utc::now().timestamp();
+        let token = sign(&secret(), user_id, EMAIL, 24);
+        assert!(token.starts_with(&format!("{user_id}.")));
+        assert!(accepts(&token, EMAIL, now));
+        let expires_at = now + 3600;
+        let token = signed_token(&secret(), user_id, EMAIL, expires_at);
+        assert!(accepts(&token, EMAIL, expires_at));
+        assert!(!accepts(&token, EMAIL, expires_at + 1));
+        // already expired when signed
+        assert!(!accepts(&sign(&secret(), user_id, EMAIL, -1), EMAIL, now));
+    }
+
+    #[test]
+    fn tampered_tokens_are_refused() {
+        let user_id = Uuid::new_v4();
+        let now = chrono::Utc::now().timestamp();
+        let expires_at = now + 3600;
+        let token = signed_token(&secret(), user_id, EMAIL, expires_at);
+        let signature = token.rsplit('.').next().unwrap();
+        // another user, a later expiry, or the address changed since
+        let other_user = format!("{}.{expires_at}.{signature}", Uuid::new_v4());
+        assert!(!accepts(&other_user, EMAIL, now));
+        let later = format!("{user_id}.{}.{signature}", expires_at + 86_400);
+        assert!(!accepts(&later, EMAIL, now));
+        assert!(!accepts(&token, "eve@example.com", now));
+        // another secret
+        let forged = sign(&SecretString::new("guessed".into()), user_id, EMAIL, 24);
+        assert!(!accepts(&forged, EMAIL, now));
+        // a changed, cut or badly encoded signature
+        let flipped = match signature.chars().last() {
+            Some('0') => '1',
+            _ => '0',
+        };
+        let changed = format!("{}{flipped}", &token[..token.len() - 1]);
+        assert!(!accepts(&changed, EMAIL, now));
+        assert!(!accepts(&token[..token.len() - 2], EMAIL, now));
+        assert!(!accepts(&token[..token.len() - 1], EMAIL, now));
+        assert!(!accepts(
+            &format!("{}zz", &token[..token.len() - 2]),
+            EMAIL,
+            now
+        ));
+        for malformed in ["", "..", "not.a.token", &format!("{user_id}.{expires_at}")] {
+            assert!(SignedToken::parse(malformed).is_none(), "{malformed:?}");
+        }
+    }
+}
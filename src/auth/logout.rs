@@ -1,7 +1,12 @@
-use super::mw_auth::AUTH_COOKIE;
+use super::{
+    mw_auth::AUTH_COOKIE,
+    session::{end_session, end_sessions, session_id},
+    session_key::SessionKey,
+};
 use crate::{app_state::RedisConnection, ctx::Ctx};
 use redis::AsyncCommands;
 use tower_cookies::Cookies;
+use uuid::Uuid;
 
 pub async fn logout(
     ctx: Ctx,
@@ -9,6 +14,21 @@ pub async fn logout(
     mut redis_connection: RedisConnection<'_>,
 ) -> anyhow::Result<()> {
     cookies.remove(AUTH_COOKIE.into());
-    redis_connection.del(ctx.session_id()).await?;
+    let _: () = redis_connection.del(ctx.session_id()).await?;
+    end_session(
+        &mut redis_connection,
+        ctx.user_id(),
+        &session_id(&ctx.session_id()),
+    )
+    .await?;
     Ok(())
 }
+
+/// End every session of the user but the `kept` one.
+pub async fn logout_sessions(
+    user_id: Uuid,
+    kept: Option<&SessionKey>,
+    mut redis_connection: RedisConnection<'_>,
+) -> anyhow::Result<()> {
+    end_sessions(&mut redis_connection, user_id, kept).await
+}
@@ -1,5 +1,10 @@
+pub mod email_verification;
 pub mod error;
 pub mod logout;
 pub mod mw_auth;
 pub mod password;
+pub mod password_reset;
+pub mod session;
 pub mod session_key;
+pub mod throttle;
+pub mod totp;
@@ -11,7 +11,7 @@ use bb8_redis::redis::AsyncCommands;
 use tower_cookies::{cookie::time::Duration, Cookie, Cookies};
 use uuid::Uuid;
 
-use super::session_key::SessionKey;
+use super::{session::touch_session, session_key::SessionKey};
 
 pub type CtxResult = Result<Ctx, CtxExtError>;
 pub const AUTH_COOKIE: &str = "x-session";
@@ -69,6 +69,9 @@ async fn ctx_resolve(state: SharedAppState, cookies: &Cookies) -> CtxResult {
         .get_ex(&session_key, redis::Expiry::EX(10))
         .await
         .map_err(|_| CtxExtError::SessionNotFound)?;
+    if let Err(e) = touch_session(&mut conn, user_id, &session_key).await {
+        tracing::error!("Failed updating session details: {}", e);
+    }
     Ctx::new(user_id, session_key).map_err(|_| CtxExtError::CtxCreateFail(user_id.to_string()))
 }
 
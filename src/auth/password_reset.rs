@@ -0,0 +1,137 @@
+use crate::app_state::RedisConnection;
+use anyhow::Context;
+use bb8_redis::redis::{AsyncCommands, ExistenceCheck, SetExpiry, SetOptions};
+use chrono::{DateTime, Duration, Utc};
+use rand::{distributions::Alphanumeric, rngs::OsRng, Rng as _};
+use sha2::{Digest, Sha256};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+/// How long a reset link can be used.
+pub const TOKEN_TTL_MINUTES: i64 = 60;
+/// Seconds before another reset link can be mailed to the same account.
+pub const RESEND_SECONDS: u64 = 60;
+
+fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+fn new_token() -> String {
+    (0..48)
+        .map(|_| OsRng.sample(Alphanumeric) as char)
+        .collect()
+}
+
+/// Until when a token made at `now` can be used.
+fn expires_at(now: DateTime<Utc>) -> DateTime<Utc> {
+    now + Duration::minutes(TOKEN_TTL_MINUTES)
+}
+
+fn is_expired(expires_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    expires_at <= now
+}
+
+/// Start a reset for the user with this username or email, returning where to mail the token
+/// and the token. Links sent before stop working. `None` when there is no such user, or when a
+/// link was sent to it less than `RESEND_SECONDS` ago.
+pub async fn create_token(
+    pool: &PgPool,
+    conn: &mut RedisConnection<'_>,
+    email_or_user: &str,
+) -> anyhow::Result<Option<(String, String)>> {
+    let Some(user) = sqlx::query!(
+        "SELECT id, email FROM tbl_user WHERE username = $1 OR email = $1",
+        email_or_user.trim(),
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed fetching user")?
+    else {
+        return Ok(None);
+    };
+    let opts = SetOptions::default()
+        .conditional_set(ExistenceCheck::NX)
+        .with_expiration(SetExpiry::EX(RESEND_SECONDS));
+    let set: Option<String> = conn
+        .set_options(format!("password_reset_sent:{}", user.id), 1, opts)
+        .await
+        .context("Failed limiting reset mails")?;
+    if set.is_none() {
+        return Ok(None);
+    }
+    let token = new_token();
+    sqlx::query!(
+        r#"WITH previous AS (
+            DELETE FROM tbl_password_reset WHERE user_id = $2 OR expires_at < NOW()
+        )
+        INSERT INTO tbl_password_reset(token_hash, user_id, expires_at)
+        VALUES ($1, $2, $3)"#,
+        hash_token(&token),
+        user.id,
+        expires_at(Utc::now()),
+    )
+    .execute(pool)
+    .await
+    .context("Failed saving reset token")?;
+    Ok(Some((user.email, token)))
+}
+
+/// Whether a token can still be used, without using it.
+pub async fn is_token_valid(pool: &PgPool, token: &str) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(
+        "SELECT expires_at FROM tbl_password_reset WHERE token_hash = $1",
+        hash_token(token),
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.is_some_and(|row| !is_expired(row.expires_at, Utc::now())))
+}
+
+/// Use a token, returning its user when it was valid. It can't be used again.
+pub async fn consume_token(
+    connection: &mut PgConnection,
+    token: &str,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"DELETE FROM tbl_password_reset
+        WHERE token_hash = $1
+        RETURNING user_id, expires_at"#,
+        hash_token(token),
+    )
+    .fetch_optional(connection)
+    .await?;
+    Ok(row
+        .filter(|row| !is_expired(row.expires_at, Utc::now()))
+        .map(|row| row.user_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_sha256_of_the_token_is_kept() {
+        // the sha256 test vector of "abc"
+        assert_eq!(
+            hash_token("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        let token = new_token();
+        assert_eq!(token.len(), 48);
+        assert!(token.bytes().all(|byte| byte.is_ascii_alphanumeric()));
+        assert_ne!(hash_token(&token), token);
+        assert_eq!(hash_token(&token), hash_token(&token));
+        assert_ne!(new_token(), token);
+    }
+
+    #[test]
+    fn tokens_expire_after_the_ttl() {
+        let now = Utc::now();
+        let expires_at = expires_at(now);
+        assert_eq!(expires_at - now, Duration::minutes(TOKEN_TTL_MINUTES));
+        assert!(!is_expired(expires_at, now));
+        assert!(!is_expired(expires_at, expires_at - Duration::seconds(1)));
+        assert!(is_expired(expires_at, expires_at));
+        assert!(is_expired(expires_at, expires_at + Duration::seconds(1)));
+    }
+}
@@ -0,0 +1,291 @@
+use super::{
+    mw_auth::AUTH_COOKIE,
+    session_key::{generate_session_key, SessionKey},
+};
+use crate::app_state::RedisConnection;
+use axum::http::{header::USER_AGENT, HeaderMap};
+use bb8_redis::redis::{self, AsyncCommands, ExistenceCheck, SetExpiry, SetOptions};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+};
+use tower_cookies::{cookie::time::Duration, Cookie, Cookies};
+use uuid::Uuid;
+
+pub const PENDING_LOGIN_COOKIE: &str = "x-login-pending";
+/// Seconds left to enter the second factor after the password.
+const PENDING_LOGIN_TTL: u64 = 300;
+/// Wrong codes accepted before the password has to be entered again.
+const PENDING_LOGIN_ATTEMPTS: i64 = 5;
+/// Seconds the details of a session outlive its last request, longer than the session itself so
+/// they are only removed along with it.
+const SESSION_INFO_TTL: i64 = 60;
+
+/// Where a session is used from, shown to the user to recognise it.
+pub struct Client {
+    pub ip: IpAddr,
+    pub user_agent: String,
+}
+
+impl Client {
+    pub fn new(addr: SocketAddr, headers: &HeaderMap) -> Self {
+        let user_agent = headers
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .chars()
+            .take(256)
+            .collect();
+        Self {
+            ip: addr.ip(),
+            user_agent,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct SessionInfo {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub ip: String,
+    pub user_agent: String,
+    /// The session of the request.
+    pub current: bool,
+}
+
+/// The id a session is shown and revoked with, so the key itself is never handed out.
+pub fn session_id(session_key: &SessionKey) -> String {
+    let hash = Sha256::digest(session_key.as_ref().as_bytes());
+    format!("{hash:x}")[..32].to_string()
+}
+
+fn index_key(user_id: Uuid) -> String {
+    format!("user_sessions:{user_id}")
+}
+
+fn info_key(id: &str) -> String {
+    format!("session_info:{id}")
+}
+
+/// Store a new session of the user and hand its key to the browser.
+pub async fn start_session(
+    redis_connection: &mut RedisConnection<'_>,
+    cookies: &Cookies,
+    user_id: Uuid,
+    client: Client,
+) -> anyhow::Result<()> {
+    let opts = SetOptions::default()
+        .conditional_set(ExistenceCheck::NX)
+        .with_expiration(SetExpiry::EX(60));
+    let session_key = generate_session_key();
+    let _: () = redis_connection
+        .set_options(&session_key, user_id, opts)
+        .await?;
+    let id = session_id(&session_key);
+    let now = Utc::now().timestamp();
+    let _: () = redis::pipe()
+        .hset_multiple(
+            info_key(&id),
+            &[
+                ("key", session_key.as_ref().to_string()),
+                ("created_at", now.to_string()),
+                ("last_seen", now.to_string()),
+                ("ip", client.ip.to_string()),
+                ("user_agent", client.user_agent),
+            ],
+        )
+        .ignore()
+        .expire(info_key(&id), SESSION_INFO_TTL)
+        .ignore()
+        .sadd(index_key(user_id), &id)
+        .ignore()
+        .expire(index_key(user_id), SESSION_INFO_TTL)
+        .ignore()
+        .query_async(&mut **redis_connection)
+        .await?;
+    let mut auth_cookie = Cookie::new(AUTH_COOKIE, session_key.as_ref().to_string());
+    auth_cookie.set_max_age(Duration::seconds(10));
+    auth_cookie.set_http_only(true);
+    cookies.add(auth_cookie);
+    Ok(())
+}
+
+/// Note a request of the session, keeping its details as long as it lives.
+pub async fn touch_session(
+    redis_connection: &mut RedisConnection<'_>,
+    user_id: Uuid,
+    session_key: &SessionKey,
+) -> anyhow::Result<()> {
+    let id = session_id(session_key);
+    let _: () = redis::pipe()
+        .hset(info_key(&id), "last_seen", Utc::now().timestamp())
+        .ignore()
+        .expire(info_key(&id), SESSION_INFO_TTL)
+        .ignore()
+        .expire(index_key(user_id), SESSION_INFO_TTL)
+        .ignore()
+        .query_async(&mut **redis_connection)
+        .await?;
+    Ok(())
+}
+
+/// The live sessions of the user, the last used first. Sessions that expired are dropped from
+/// the index on the way.
+pub async fn list_sessions(
+    redis_connection: &mut RedisConnection<'_>,
+    user_id: Uuid,
+    current: &SessionKey,
+) -> anyhow::Result<Vec<SessionInfo>> {
+    let current = session_id(current);
+    let ids: Vec<String> = redis_connection.smembers(index_key(user_id)).await?;
+    let mut sessions = Vec::new();
+    for id in ids {
+        let info: HashMap<String, String> = redis_connection.hgetall(info_key(&id)).await?;
+        let alive = match info.get("key") {
+            Some(key) => redis_connection.exists(key).await?,
+            None => false,
+        };
+        if !alive {
+            let _: () = redis_connection.del(info_key(&id)).await?;
+            let _: () = redis_connection.srem(index_key(user_id), &id).await?;
+            continue;
+        }
+        let time = |field: &str| {
+            info.get(field)
+                .and_then(|value| value.parse().ok())
+                .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+                .unwrap_or_default()
+        };
+        sessions.push(SessionInfo {
+            created_at: time("created_at"),
+            last_seen: time("last_seen"),
+            ip: info.get("ip").cloned().unwrap_or_default(),
+            user_agent: info.get("user_agent").cloned().unwrap_or_default(),
+            current: id == current,
+            id,
+        });
+    }
+    sessions.sort_by_key(|session| std::cmp::Reverse(session.last_seen));
+    Ok(sessions)
+}
+
+/// End a session of the user by its id, returns whether the user had it.
+pub async fn end_session(
+    redis_connection: &mut RedisConnection<'_>,
+    user_id: Uuid,
+    id: &str,
+) -> anyhow::Result<bool> {
+    let removed: i64 = redis_connection.srem(index_key(user_id), id).await?;
+    if removed == 0 {
+        return Ok(false);
+    }
+    let key: Option<String> = redis_connection.hget(info_key(id), "key").await?;
+    if let Some(key) = key {
+        let _: () = redis_connection.del(&[key, info_key(id)]).await?;
+    }
+    Ok(true)
+}
+
+/// End every session of the user but the `kept` one.
+pub async fn end_sessions(
+    redis_connection: &mut RedisConnection<'_>,
+    user_id: Uuid,
+    kept: Option<&SessionKey>,
+) -> anyhow::Result<()> {
+    let kept = kept.map(session_id);
+    let ids: Vec<String> = redis_connection.smembers(index_key(user_id)).await?;
+    for id in ids {
+        if kept.as_ref() != Some(&id) {
+            end_session(redis_connection, user_id, &id).await?;
+        }
+    }
+    Ok(())
+}
+
+fn pending_key(id: &str) -> String {
+    format!("login_pending:{id}")
+}
+
+/// Remember a user who gave their password and still has to give their second factor.
+pub async fn start_pending_login(
+    redis_connection: &mut RedisConnection<'_>,
+    cookies: &Cookies,
+    user_id: Uuid,
+) -> anyhow::Result<()> {
+    let id = generate_session_key();
+    let _: () = redis_connection
+        .set_ex(pending_key(id.as_ref()), user_id, PENDING_LOGIN_TTL)
+        .await?;
+    let mut cookie = Cookie::new(PENDING_LOGIN_COOKIE, String::from(id));
+    cookie.set_max_age(Duration::seconds(PENDING_LOGIN_TTL as i64));
+    cookie.set_http_only(true);
+    cookies.add(cookie);
+    Ok(())
+}
+
+/// The user of the login waiting for its second factor, counting an attempt. `None` once it
+/// expired or ran out of attempts.
+pub async fn pending_login_attempt(
+    redis_connection: &mut RedisConnection<'_>,
+    cookies: &Cookies,
+) -> anyhow::Result<Option<Uuid>> {
+    let Some(cookie) = cookies.get(PENDING_LOGIN_COOKIE) else {
+        return Ok(None);
+    };
+    let key = pending_key(cookie.value());
+    let user_id: Option<Uuid> = redis_connection.get(&key).await?;
+    if user_id.is_none() {
+        return Ok(None);
+    }
+    let attempts_key = format!("{key}:attempts");
+    let attempts: i64 = redis_connection.incr(&attempts_key, 1).await?;
+    let _: () = redis_connection
+        .expire(&attempts_key, PENDING_LOGIN_TTL as i64)
+        .await?;
+    if attempts > PENDING_LOGIN_ATTEMPTS {
+        end_pending_login(redis_connection, cookies).await?;
+        return Ok(None);
+    }
+    Ok(user_id)
+}
+
+pub async fn end_pending_login(
+    redis_connection: &mut RedisConnection<'_>,
+    cookies: &Cookies,
+) -> anyhow::Result<()> {
+    if let Some(cookie) = cookies.get(PENDING_LOGIN_COOKIE) {
+        let key = pending_key(cookie.value());
+        let _: () = redis_connection
+            .del(&[format!("{key}:attempts"), key])
+            .await?;
+        cookies.remove(Cookie::from(PENDING_LOGIN_COOKIE));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_id_is_the_start_of_the_sha256_of_the_key() {
+        let key = SessionKey::try_from("abc").unwrap();
+        // the sha256 test vector of "abc"
+        assert_eq!(session_id(&key), "ba7816bf8f01cfea414140de5dae2223");
+        assert_eq!(session_id(&key), session_id(&key.clone()));
+    }
+
+    #[test]
+    fn session_id_does_not_reveal_the_key() {
+        let key = generate_session_key();
+        let id = session_id(&key);
+        assert_eq!(id.len(), 32);
+        assert!(id.bytes().all(|byte| byte.is_ascii_hexdigit()));
+        assert!(!id.contains(key.as_ref()) && !key.as_ref().contains(&id));
+        assert_ne!(session_id(&generate_session_key()), id);
+        assert!(!info_key(&id).contains(key.as_ref()));
+    }
+}
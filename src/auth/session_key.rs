@@ -52,7 +52,7 @@ impl ToRedisArgs for SessionKey {
     where
         W: ?Sized + redis::RedisWrite,
     {
-        out.write_arg_fmt("{self}");
+        out.write_arg(self.0.as_bytes());
     }
 }
 
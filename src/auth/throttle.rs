@@ -0,0 +1,319 @@
+//! Slow down, then lock out, repeated failed logins. Failures are counted over a sliding window
+//! for the account tried and for the address trying it, wrong passwords and wrong second factors
+//! alike.
+use crate::{app_state::RedisConnection, configuration::LoginThrottleSettings};
+use axum::{
+    http::{header::RETRY_AFTER, StatusCode},
+    response::{IntoResponse, Response},
+};
+use bb8_redis::redis::AsyncCommands;
+use sqlx::PgPool;
+use std::net::IpAddr;
+use uuid::Uuid;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ThrottleError {
+    /// Seconds before another login can be tried.
+    #[error("too many failed logins, try again later")]
+    Throttled(u64),
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl From<bb8_redis::redis::RedisError> for ThrottleError {
+    fn from(e: bb8_redis::redis::RedisError) -> Self {
+        Self::UnexpectedError(e.into())
+    }
+}
+
+impl From<sqlx::Error> for ThrottleError {
+    fn from(e: sqlx::Error) -> Self {
+        Self::UnexpectedError(e.into())
+    }
+}
+
+impl IntoResponse for ThrottleError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Throttled(seconds) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(RETRY_AFTER, seconds.to_string())],
+                self.to_string(),
+            )
+                .into_response(),
+            Self::UnexpectedError(e) => {
+                tracing::error!("Failed login throttling: {:?}", e);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        }
+    }
+}
+
+/// What failures are counted for.
+struct Subject {
+    kind: &'static str,
+    id: String,
+    max_failures: u32,
+}
+
+impl Subject {
+    fn account(settings: &LoginThrottleSettings, account: &Account) -> Self {
+        let (kind, id) = match account {
+            Account::User(user_id) => ("user", user_id.to_string()),
+            Account::Unknown(login) => ("login", login.clone()),
+        };
+        Self {
+            kind,
+            id,
+            max_failures: settings.max_user_failures,
+        }
+    }
+
+    fn address(settings: &LoginThrottleSettings, ip: IpAddr) -> Self {
+        Self {
+            kind: "ip",
+            id: ip.to_string(),
+            max_failures: settings.max_ip_failures,
+        }
+    }
+
+    fn failures_key(&self) -> String {
+        format!("login_failures:{}:{}", self.kind, self.id)
+    }
+
+    fn lockout_key(&self) -> String {
+        format!("login_lockout:{}:{}", self.kind, self.id)
+    }
+
+    fn is_locked_out(&self, failures: u32) -> bool {
+        failures >= self.max_failures
+    }
+}
+
+/// Failures at or before this time, in milliseconds, are no longer counted.
+fn window_start(settings: &LoginThrottleSettings, now: i64) -> i64 {
+    now - settings.window_minutes * 60_000
+}
+
+/// Seconds to wait before the next attempt, given the times of the failures in milliseconds
+/// and oldest first. Each failure past the free ones doubles the wait after the last one.
+fn backoff_seconds(settings: &LoginThrottleSettings, failures: &[i64], now: i64) -> u64 {
+    let start = window_start(settings, now);
+    let counted: Vec<i64> = failures.iter().copied().filter(|at| *at > start).collect();
+    let Some(last) = counted.last() else {
+        return 0;
+    };
+    let count = counted.len() as u32;
+    if count < settings.free_failures {
+        return 0;
+    }
+    let backoff = 1000_i64 << (count - settings.free_failures).min(20);
+    let left = last + backoff - now;
+    if left > 0 {
+        ((left + 999) / 1000) as u64
+    } else {
+        0
+    }
+}
+
+/// The account a login is counted against.
+#[derive(Clone, Debug)]
+pub enum Account {
+    /// An existing user, whether their username or their email was typed.
+    User(Uuid),
+    /// A login matching no user, `Bob ` and `bob` count as the same.
+    Unknown(String),
+}
+
+impl Account {
+    /// Find the user logging in with this username or email, whatever their case, so that
+    /// retyping the login differently doesn't start a new count.
+    pub async fn resolve(pool: &PgPool, login: &str) -> Result<Self, ThrottleError> {
+        let login = normalize_login(login);
+        let user = sqlx::query!(
+            r#"SELECT id FROM tbl_user WHERE LOWER(username) = $1 OR LOWER(email) = $1
+            ORDER BY created_at LIMIT 1"#,
+            login,
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(match user {
+            Some(user) => Self::User(user.id),
+            None => Self::Unknown(login),
+        })
+    }
+}
+
+/// Logins are compared trimmed and in lower case.
+fn normalize_login(login: &str) -> String {
+    login.trim().to_lowercase()
+}
+
+/// Refuse the attempt while the account or the address is locked out or backing off.
+pub async fn check(
+    conn: &mut RedisConnection<'_>,
+    settings: &LoginThrottleSettings,
+    account: &Account,
+    ip: IpAddr,
+) -> Result<(), ThrottleError> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut wait = 0;
+    for subject in [
+        Subject::account(settings, account),
+        Subject::address(settings, ip),
+    ] {
+        let locked: i64 = conn.ttl(subject.lockout_key()).await?;
+        if locked > 0 {
+            wait = wait.max(locked as u64);
+            continue;
+        }
+        let key = subject.failures_key();
+        let _: () = conn
+            .zrembyscore(&key, "-inf", window_start(settings, now))
+            .await?;
+        let failures: Vec<(String, f64)> = conn.zrange_withscores(&key, 0, -1).await?;
+        let failures: Vec<i64> = failures.into_iter().map(|(_, at)| at as i64).collect();
+        wait = wait.max(backoff_seconds(settings, &failures, now));
+    }
+    match wait {
+        0 => Ok(()),
+        wait => Err(ThrottleError::Throttled(wait)),
+    }
+}
+
+/// Count a failed login, locking out the account or the address once it had too many.
+pub async fn record_failure(
+    conn: &mut RedisConnection<'_>,
+    settings: &LoginThrottleSettings,
+    account: &Account,
+    ip: IpAddr,
+) -> Result<(), ThrottleError> {
+    let now = chrono::Utc::now().timestamp_millis();
+    for subject in [
+        Subject::account(settings, account),
+        Subject::address(settings, ip),
+    ] {
+        let key = subject.failures_key();
+        let _: () = conn
+            .zrembyscore(&key, "-inf", window_start(settings, now))
+            .await?;
+        let _: () = conn.zadd(&key, Uuid::new_v4().to_string(), now).await?;
+        let _: () = conn.expire(&key, settings.window_minutes * 60).await?;
+        let failures: u32 = conn.zcard(&key).await?;
+        if subject.is_locked_out(failures) {
+            let seconds = settings.lockout_minutes * 60;
+            let _: () = conn.set_ex(subject.lockout_key(), 1, seconds).await?;
+            // the lockout starts a new count once over
+            let _: () = conn.del(&key).await?;
+            tracing::warn!(
+                kind = subject.kind,
+                subject = %subject.id,
+                failures,
+                lockout_seconds = seconds,
+                "Login locked out"
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Forget the failures of an account once logged in, the address keeps its count.
+pub async fn record_success(
+    conn: &mut RedisConnection<'_>,
+    settings: &LoginThrottleSettings,
+    account: &Account,
+) -> Result<(), ThrottleError> {
+    let _: () = conn
+        .del(Subject::account(settings, account).failures_key())
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000_000;
+
+    fn settings() -> LoginThrottleSettings {
+        LoginThrottleSettings {
+            window_minutes: 15,
+            free_failures: 3,
+            max_user_failures: 10,
+            max_ip_failures: 50,
+            lockout_minutes: 15,
+        }
+    }
+
+    #[test]
+    fn backoff_starts_after_the_free_failures_and_doubles() {
+        let settings = settings();
+        assert_eq!(backoff_seconds(&settings, &[], NOW), 0);
+        assert_eq!(backoff_seconds(&settings, &[NOW, NOW], NOW), 0);
+        assert_eq!(backoff_seconds(&settings, &[NOW, NOW, NOW], NOW), 1);
+        assert_eq!(backoff_seconds(&settings, &[NOW; 4], NOW), 2);
+        assert_eq!(backoff_seconds(&settings, &[NOW; 6], NOW), 8);
+        // counted from the last failure, rounded up to the second
+        assert_eq!(backoff_seconds(&settings, &[NOW; 6], NOW + 7_500), 1);
+        assert_eq!(backoff_seconds(&settings, &[NOW; 6], NOW + 8_000), 0);
+        // capped instead of overflowing
+        assert_eq!(backoff_seconds(&settings, &[NOW; 100], NOW), 1 << 20);
+    }
+
+    #[test]
+    fn failures_expire_with_the_window() {
+        let settings = settings();
+        let window = settings.window_minutes * 60_000;
+        let failures = [NOW - window, NOW - window + 1, NOW - 1_000, NOW];
+        // the first is exactly a window old and no longer counted
+        assert_eq!(backoff_seconds(&settings, &failures, NOW), 1);
+        // a millisecond later the second one expires too, leaving 2 free failures
+        assert_eq!(backoff_seconds(&settings, &failures, NOW + 1), 0);
+        assert_eq!(window_start(&settings, NOW), NOW - window);
+    }
+
+    #[test]
+    fn accounts_and_addresses_lock_out_at_their_own_threshold() {
+        let settings = settings();
+        let user = Subject::account(&settings, &Account::User(Uuid::nil()));
+        assert!(!user.is_locked_out(9));
+        assert!(user.is_locked_out(10));
+        let address = Subject::address(&settings, "127.0.0.1".parse().unwrap());
+        assert!(!address.is_locked_out(49));
+        assert!(address.is_locked_out(50));
+        assert_ne!(user.lockout_key(), address.lockout_key());
+    }
+
+    #[test]
+    fn success_clears_the_count_of_the_account_only() {
+        // `record_success` deletes the key `check` and `record_failure` count the account in
+        let settings = settings();
+        let account = Account::User(Uuid::nil());
+        let ip = "127.0.0.1".parse().unwrap();
+        assert_eq!(
+            Subject::account(&settings, &account).failures_key(),
+            format!("login_failures:user:{}", Uuid::nil())
+        );
+        assert_ne!(
+            Subject::account(&settings, &account).failures_key(),
+            Subject::address(&settings, ip).failures_key()
+        );
+    }
+
+    #[test]
+    fn unknown_logins_share_one_bucket() {
+        let settings = settings();
+        let key = |login: &str| {
+            Subject::account(&settings, &Account::Unknown(normalize_login(login))).failures_key()
+        };
+        assert_eq!(key("Bob"), key("bob"));
+        assert_eq!(key(" BOB\t"), key("bob"));
+        assert_ne!(key("bob"), key("alice"));
+        // an unknown login can't share the count of a user
+        let user_id = Uuid::nil();
+        assert_ne!(
+            key(&user_id.to_string()),
+            Subject::account(&settings, &Account::User(user_id)).failures_key()
+        );
+    }
+}
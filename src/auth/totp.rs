@@ -0,0 +1,358 @@
+//! RFC 6238 time-based one-time passwords, with SHA-1, 6 digits and 30 second steps as
+//! authenticator apps expect.
+use super::throttle::ThrottleError;
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use qrcode::{render::svg, QrCode};
+use rand::{rngs::OsRng, Rng as _, RngCore as _};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const ISSUER: &str = "TicketApp";
+const STEP_SECONDS: i64 = 30;
+const RECOVERY_CODES: usize = 10;
+/// No 0/o or 1/l, recovery codes are typed by hand.
+const RECOVERY_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+#[derive(thiserror::Error, Debug)]
+pub enum TotpError {
+    #[error("{0}")]
+    Invalid(&'static str),
+    #[error(transparent)]
+    Throttled(#[from] ThrottleError),
+    #[error(transparent)]
+    UnexpectedError(#[from] sqlx::Error),
+}
+
+impl IntoResponse for TotpError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Invalid(message) => (StatusCode::BAD_REQUEST, message).into_response(),
+            Self::Throttled(e) => e.into_response(),
+            Self::UnexpectedError(e) => {
+                tracing::error!("Failed two-factor operation: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        }
+    }
+}
+
+fn hotp(key: &[u8], counter: u64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(&counter.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let value = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+    value % 1_000_000
+}
+
+/// The code of a base32 secret at a unix time.
+pub fn code_at(secret: &str, unix_time: i64) -> Option<String> {
+    let key = BASE32_NOPAD.decode(secret.as_bytes()).ok()?;
+    Some(format!(
+        "{:06}",
+        hotp(&key, (unix_time / STEP_SECONDS) as u64)
+    ))
+}
+
+/// The time step a code was made for, one step of clock drift is accepted either way.
+fn matching_step(secret: &str, code: &str, unix_time: i64) -> Option<i64> {
+    if code.len() != 6 || !code.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let key = BASE32_NOPAD.decode(secret.as_bytes()).ok()?;
+    let code: u32 = code.parse().ok()?;
+    let step = unix_time / STEP_SECONDS;
+    (step - 1..=step + 1).find(|step| hotp(&key, *step as u64) == code)
+}
+
+/// Codes are often typed or pasted with spaces.
+fn clean_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+fn hash_recovery_code(code: &str) -> String {
+    format!("{:x}", Sha256::digest(clean_code(code).as_bytes()))
+}
+
+/// Where an authenticator app reads the secret from.
+pub fn otpauth_uri(account: &str, secret: &str) -> String {
+    let account: String = format!("{ISSUER}:{account}")
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b':' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect();
+    format!(
+        "otpauth://totp/{account}?secret={secret}&issuer={ISSUER}\
+        &algorithm=SHA1&digits=6&period={STEP_SECONDS}"
+    )
+}
+
+/// The URI as a QR code to scan, an SVG image.
+pub fn qr_svg(uri: &str) -> String {
+    QrCode::new(uri.as_bytes())
+        .map(|code| code.render::<svg::Color>().min_dimensions(200, 200).build())
+        .unwrap_or_default()
+}
+
+pub async fn is_enabled(pool: &PgPool, user_id: Uuid) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(
+        "SELECT user_id FROM tbl_totp WHERE user_id = $1 AND confirmed_at IS NOT NULL",
+        user_id,
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.is_some())
+}
+
+/// Make a new secret for the user to scan, it is only used once confirmed with a code.
+pub async fn start_enrollment(pool: &PgPool, user_id: Uuid) -> Result<String, TotpError> {
+    let mut key = [0u8; 20];
+    OsRng.fill_bytes(&mut key);
+    let secret = BASE32_NOPAD.encode(&key);
+    let result = sqlx::query!(
+        r#"INSERT INTO tbl_totp(user_id, secret) VALUES ($1, $2)
+        ON CONFLICT (user_id) DO UPDATE SET secret = EXCLUDED.secret, created_at = NOW()
+        WHERE tbl_totp.confirmed_at IS NULL"#,
+        user_id,
+        secret,
+    )
+    .execute(pool)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(TotpError::Invalid(
+            "two-factor authentication is already on",
+        ));
+    }
+    Ok(secret)
+}
+
+/// Replace the recovery codes of the user, returning the new ones to show once.
+async fn new_recovery_codes(
+    connection: &mut sqlx::PgConnection,
+    user_id: Uuid,
+) -> Result<Vec<String>, sqlx::Error> {
+    let codes: Vec<String> = (0..RECOVERY_CODES)
+        .map(|_| {
+            let code: String = (0..10)
+                .map(|_| RECOVERY_ALPHABET[OsRng.gen_range(0..RECOVERY_ALPHABET.len())] as char)
+                .collect();
+            format!("{}-{}", &code[..5], &code[5..])
+        })
+        .collect();
+    let hashes: Vec<String> = codes.iter().map(|code| hash_recovery_code(code)).collect();
+    sqlx::query!("DELETE FROM tbl_recovery_code WHERE user_id = $1", user_id)
+        .execute(&mut *connection)
+        .await?;
+    sqlx::query!(
+        r#"INSERT INTO tbl_recovery_code(user_id, code_hash)
+        SELECT $1, * FROM UNNEST($2::text[])"#,
+        user_id,
+        &hashes,
+    )
+    .execute(&mut *connection)
+    .await?;
+    Ok(codes)
+}
+
+/// Turn on the second factor with a first code from the app, returning the recovery codes.
+pub async fn confirm_enrollment(
+    pool: &PgPool,
+    user_id: Uuid,
+    code: &str,
+) -> Result<Vec<String>, TotpError> {
+    let mut transaction = pool.begin().await?;
+    let secret = sqlx::query_scalar!(
+        "SELECT secret FROM tbl_totp WHERE user_id = $1 AND confirmed_at IS NULL FOR UPDATE",
+        user_id,
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    .ok_or(TotpError::Invalid("no two-factor setup started"))?;
+    let step = matching_step(&secret, &clean_code(code), chrono::Utc::now().timestamp())
+        .ok_or(TotpError::Invalid("wrong code"))?;
+    sqlx::query!(
+        "UPDATE tbl_totp SET confirmed_at = NOW(), last_step = $2 WHERE user_id = $1",
+        user_id,
+        step,
+    )
+    .execute(&mut *transaction)
+    .await?;
+    let codes = new_recovery_codes(&mut transaction, user_id).await?;
+    transaction.commit().await?;
+    Ok(codes)
+}
+
+/// Check a code from the app, or use up a recovery code.
+pub async fn verify(pool: &PgPool, user_id: Uuid, code: &str) -> Result<bool, sqlx::Error> {
+    let code = clean_code(code);
+    let Some(secret) = sqlx::query_scalar!(
+        "SELECT secret FROM tbl_totp WHERE user_id = $1 AND confirmed_at IS NOT NULL",
+        user_id,
+    )
+    .fetch_optional(pool)
+    .await?
+    else {
+        return Ok(false);
+    };
+    if let Some(step) = matching_step(&secret, &code, chrono::Utc::now().timestamp()) {
+        // a code seen once, or an older one, is refused
+        let result = sqlx::query!(
+            r#"UPDATE tbl_totp SET last_step = $2
+            WHERE user_id = $1 AND (last_step IS NULL OR last_step < $2)"#,
+            user_id,
+            step,
+        )
+        .execute(pool)
+        .await?;
+        return Ok(result.rows_affected() == 1);
+    }
+    let result = sqlx::query!(
+        r#"UPDATE tbl_recovery_code SET used_at = NOW()
+        WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL"#,
+        user_id,
+        hash_recovery_code(&code),
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Replace the recovery codes, a valid code is asked first.
+pub async fn regenerate_recovery_codes(
+    pool: &PgPool,
+    user_id: Uuid,
+    code: &str,
+) -> Result<Vec<String>, TotpError> {
+    if !verify(pool, user_id, code).await? {
+        return Err(TotpError::Invalid("wrong code"));
+    }
+    let mut transaction = pool.begin().await?;
+    let codes = new_recovery_codes(&mut transaction, user_id).await?;
+    transaction.commit().await?;
+    Ok(codes)
+}
+
+/// Turn off the second factor, a valid code is asked first.
+pub async fn disable(pool: &PgPool, user_id: Uuid, code: &str) -> Result<(), TotpError> {
+    if !verify(pool, user_id, code).await? {
+        return Err(TotpError::Invalid("wrong code"));
+    }
+    let mut transaction = pool.begin().await?;
+    sqlx::query!("DELETE FROM tbl_recovery_code WHERE user_id = $1", user_id)
+        .execute(&mut *transaction)
+        .await?;
+    sqlx::query!("DELETE FROM tbl_totp WHERE user_id = $1", user_id)
+        .execute(&mut *transaction)
+        .await?;
+    transaction.commit().await?;
+    Ok(())
+}
+
+/// How many recovery codes are left.
+pub async fn recovery_codes_left(pool: &PgPool, user_id: Uuid) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM tbl_recovery_code WHERE user_id = $1 AND used_at IS NULL"#,
+        user_id,
+    )
+    .fetch_one(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The ASCII key "12345678901234567890" of the RFC test vectors.
+    const SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn hotp_matches_rfc_4226() {
+        let expected = [
+            755224, 287082, 359152, 969429, 338314, 254676, 287922, 162583, 399871, 520489,
+        ];
+        for (counter, code) in expected.into_iter().enumerate() {
+            assert_eq!(hotp(b"12345678901234567890", counter as u64), code);
+        }
+    }
+
+    #[test]
+    fn code_at_matches_rfc_6238() {
+        // the last 6 of the 8 digits of the SHA-1 vectors
+        for (unix_time, code) in [
+            (59, "287082"),
+            (1111111109, "081804"),
+            (1111111111, "050471"),
+            (1234567890, "005924"),
+            (2000000000, "279037"),
+            (20000000000, "353130"),
+        ] {
+            assert_eq!(code_at(SECRET, unix_time).as_deref(), Some(code));
+        }
+        assert_eq!(code_at("not base32!", 59), None);
+    }
+
+    #[test]
+    fn matching_step_accepts_one_step_of_drift() {
+        let now = 1111111109;
+        let step = now / STEP_SECONDS;
+        let code = |step: i64| code_at(SECRET, step * STEP_SECONDS).unwrap();
+        assert_eq!(matching_step(SECRET, &code(step), now), Some(step));
+        assert_eq!(matching_step(SECRET, &code(step - 1), now), Some(step - 1));
+        assert_eq!(matching_step(SECRET, &code(step + 1), now), Some(step + 1));
+        assert_eq!(matching_step(SECRET, &code(step - 2), now), None);
+        assert_eq!(matching_step(SECRET, &code(step + 2), now), None);
+        // the first and last second of a step still see the steps around it
+        let first = step * STEP_SECONDS;
+        let last = first + STEP_SECONDS - 1;
+        assert_eq!(
+            matching_step(SECRET, &code(step - 1), first),
+            Some(step - 1)
+        );
+        assert_eq!(matching_step(SECRET, &code(step + 1), last), Some(step + 1));
+        assert_eq!(matching_step(SECRET, &code(step + 1), first - 1), None);
+        assert_eq!(matching_step(SECRET, &code(step - 1), last + 1), None);
+        assert_eq!(matching_step(SECRET, "12345x", now), None);
+    }
+
+    #[test]
+    fn matching_step_wants_exactly_6_digits() {
+        // "005924" is the code at 1234567890
+        let now = 1234567890;
+        assert_eq!(
+            matching_step(SECRET, "005924", now),
+            Some(now / STEP_SECONDS)
+        );
+        for code in [
+            "5924",
+            "05924",
+            "0005924",
+            "0000005924",
+            "+005924",
+            "+05924",
+            "-05924",
+            " 05924",
+        ] {
+            assert_eq!(matching_step(SECRET, code, now), None, "{code:?}");
+        }
+        assert_eq!(matching_step(SECRET, "", now), None);
+    }
+}
@@ -17,6 +17,10 @@ pub struct Settings {
     pub database: DatabaseSettings,
     pub redis: RedisSettings,
     pub logging: LoggingSettings,
+    pub attachments: AttachmentSettings,
+    pub mail: MailSettings,
+    pub email_verification: EmailVerificationSettings,
+    pub login_throttle: LoginThrottleSettings,
 }
 
 #[derive(Deserialize, Clone)]
@@ -72,8 +76,8 @@ fn protocol_from_string<'de, D>(deserializer: D) -> Result<ProtocolVersion, D::E
 where
     D: serde::Deserializer<'de>,
 {
-    let s: std::borrow::Cow<String> = Deserialize::deserialize(deserializer)?;
-    match s.as_str() {
+    let s: std::borrow::Cow<str> = Deserialize::deserialize(deserializer)?;
+    match s.as_ref() {
         "2" | "v2" | "resp2" => Ok(ProtocolVersion::RESP2),
         "3" | "v3" | "resp3" => Ok(ProtocolVersion::RESP3),
         default => Err(serde::de::Error::invalid_value(
@@ -97,6 +101,88 @@ impl RedisSettings {
     }
 }
 
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct AttachmentSettings {
+    /// Largest accepted file, in bytes.
+    pub max_size: usize,
+    /// Types an upload may have, checked against the content of the file.
+    pub mime_types: Vec<String>,
+    pub storage: StorageSettings,
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum StorageSettings {
+    Local(LocalStorageSettings),
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct LocalStorageSettings {
+    pub dir: PathBuf,
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct EmailVerificationSettings {
+    pub unverified_access: UnverifiedAccess,
+    /// Hours a confirmation link can be used.
+    pub link_ttl_hours: i64,
+    /// Seconds to wait before another confirmation mail can be sent.
+    pub resend_interval: u64,
+}
+
+/// What a user can do before confirming their email.
+#[derive(serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnverifiedAccess {
+    Full,
+    /// Only reading, anything changing data waits for the confirmation.
+    ReadOnly,
+    /// Logging in waits for the confirmation.
+    None,
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct LoginThrottleSettings {
+    /// Minutes a failed login is counted for.
+    pub window_minutes: i64,
+    /// Failures before each new attempt has to wait, twice as long as the last one.
+    pub free_failures: u32,
+    /// Failures of one account before it is locked out.
+    pub max_user_failures: u32,
+    /// Failures from one address before it is locked out.
+    pub max_ip_failures: u32,
+    pub lockout_minutes: u64,
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct MailSettings {
+    /// The `From` of every mail, `Name <address>` or a bare address.
+    pub sender: String,
+    pub transport: MailTransportSettings,
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum MailTransportSettings {
+    Smtp(SmtpSettings),
+    File(FileMailSettings),
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<SecretString>,
+    pub starttls: bool,
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct FileMailSettings {
+    /// Mails are printed to stdout when missing.
+    pub dir: Option<PathBuf>,
+}
+
 #[derive(serde::Deserialize, Clone, Debug)]
 #[serde(tag = "dest", rename_all = "lowercase")]
 pub enum LoggingSettings {
@@ -173,8 +259,8 @@ fn rotation_from_string<'de, D>(deserializer: D) -> Result<Rotation, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    let s: std::borrow::Cow<String> = Deserialize::deserialize(deserializer)?;
-    match s.as_str() {
+    let s: std::borrow::Cow<str> = Deserialize::deserialize(deserializer)?;
+    match s.as_ref() {
         "minutely" => Ok(Rotation::MINUTELY),
         "hourly" => Ok(Rotation::HOURLY),
         "daily" => Ok(Rotation::DAILY),
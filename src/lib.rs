@@ -2,8 +2,11 @@ pub mod app_state;
 pub mod auth;
 pub mod configuration;
 pub mod ctx;
+pub mod mail;
 pub mod model;
 pub mod routes;
+pub mod scheduler;
+pub mod storage;
 pub mod telemetry;
 pub mod templates;
 pub mod migration;
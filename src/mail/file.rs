@@ -0,0 +1,45 @@
+use super::{Mail, Mailer};
+use axum::async_trait;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// Writes every mail to a file of a directory, or to stdout without one.
+pub struct FileMailer {
+    sender: String,
+    dir: Option<PathBuf>,
+}
+
+impl FileMailer {
+    /// Create the directory if needed.
+    pub fn new(sender: &str, dir: Option<PathBuf>) -> std::io::Result<Self> {
+        if let Some(dir) = &dir {
+            std::fs::create_dir_all(dir)?;
+        }
+        Ok(Self {
+            sender: sender.to_string(),
+            dir,
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, mail: Mail) -> anyhow::Result<()> {
+        let content = format!(
+            "From: {}\nTo: {}\nSubject: {}\n\n{}\n",
+            self.sender, mail.to, mail.subject, mail.body
+        );
+        match &self.dir {
+            Some(dir) => {
+                let name = format!(
+                    "{}-{}.eml",
+                    chrono::Utc::now().format("%Y%m%dT%H%M%S%.6f"),
+                    Uuid::new_v4()
+                );
+                tokio::fs::write(dir.join(name), content).await?;
+            }
+            None => println!("{content}"),
+        }
+        Ok(())
+    }
+}
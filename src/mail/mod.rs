@@ -0,0 +1,35 @@
+//! How the app sends emails, the SMTP one for real use and a file one for local dev and tests.
+mod file;
+mod smtp;
+
+pub use file::FileMailer;
+pub use smtp::SmtpMailer;
+
+use crate::configuration::{MailSettings, MailTransportSettings};
+use axum::async_trait;
+use std::sync::Arc;
+
+pub type SharedMailer = Arc<dyn Mailer>;
+
+#[derive(Debug, Clone)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    /// Plain text content.
+    pub body: String,
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, mail: Mail) -> anyhow::Result<()>;
+}
+
+pub fn from_settings(settings: &MailSettings) -> anyhow::Result<SharedMailer> {
+    match &settings.transport {
+        MailTransportSettings::Smtp(smtp) => Ok(Arc::new(SmtpMailer::new(&settings.sender, smtp)?)),
+        MailTransportSettings::File(file) => Ok(Arc::new(FileMailer::new(
+            &settings.sender,
+            file.dir.clone(),
+        )?)),
+    }
+}
@@ -0,0 +1,53 @@
+use super::{Mail, Mailer};
+use crate::configuration::SmtpSettings;
+use anyhow::Context;
+use axum::async_trait;
+use lettre::{
+    message::Mailbox, transport::smtp::authentication::Credentials, AsyncSmtpTransport,
+    AsyncTransport, Message, Tokio1Executor,
+};
+use secrecy::ExposeSecret;
+
+pub struct SmtpMailer {
+    sender: Mailbox,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpMailer {
+    pub fn new(sender: &str, settings: &SmtpSettings) -> anyhow::Result<Self> {
+        let sender = sender.parse().context("Invalid mail sender")?;
+        // without STARTTLS for local relays like mailhog
+        let mut builder = if settings.starttls {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.host)?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&settings.host)
+        }
+        .port(settings.port);
+        if let (Some(username), Some(password)) = (&settings.username, &settings.password) {
+            builder = builder.credentials(Credentials::new(
+                username.clone(),
+                password.expose_secret().clone(),
+            ));
+        }
+        Ok(Self {
+            sender,
+            transport: builder.build(),
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, mail: Mail) -> anyhow::Result<()> {
+        let message = Message::builder()
+            .from(self.sender.clone())
+            .to(mail.to.parse().context("Invalid mail recipient")?)
+            .subject(mail.subject)
+            .body(mail.body)?;
+        self.transport
+            .send(message)
+            .await
+            .context("Failed sending mail")?;
+        Ok(())
+    }
+}
@@ -2,9 +2,11 @@ use std::{net::SocketAddr, sync::Arc};
 
 use askama_axum::IntoResponse;
 use axum::{
-    self, middleware,
+    self,
+    extract::DefaultBodyLimit,
+    middleware,
     response::Response,
-    routing::{get, post},
+    routing::{delete, get, post, put},
     Router,
 };
 use bb8_redis::bb8;
@@ -12,9 +14,15 @@ use bb8_redis::RedisConnectionManager;
 use sqlx::postgres::PgPoolOptions;
 use ticket_app::{
     app_state::{AppState, SharedAppState},
-    auth::mw_auth,
+    auth::{email_verification, mw_auth},
     configuration::load_settings,
-    routes::{health_check, home, index, login, logout, signup, ticket, validate},
+    routes::{
+        accounting, attachment, budget, category, change_password, currency, exchange_rate, forgot,
+        health_check, home, import, index, invitation, login, logout, member, recurring, report,
+        reset_password, session, signup, split, tag, ticket, tickets, totp, transfer, validate,
+        verify_email,
+    },
+    mail, scheduler, storage,
     telemetry::{get_subscriber, init_subscriber},
     migration::db_migration
 };
@@ -41,24 +49,124 @@ async fn main() {
         .unwrap();
     let db_pool = PgPoolOptions::new().connect_lazy_with(settings.database.with_db());
     db_migration(&db_pool).await.expect("migration script failed");
+    scheduler::spawn_recurring(db_pool.clone());
+    let blob_store = storage::from_settings(&settings.attachments.storage)
+        .expect("Failed to open the attachment storage");
+    let mailer = mail::from_settings(&settings.mail).expect("Failed to set up the mailer");
+    // leave room for the rest of the multipart body around the file
+    let attachment_body_limit = settings.attachments.max_size + 64 * 1024;
     let app_state: SharedAppState = Arc::new(AppState {
         redis_pool,
         db_pool,
         auth_secret: settings.application.auth_secret,
         base_url: settings.application.base_url,
+        blob_store,
+        attachments: settings.attachments,
+        mailer,
+        email_verification: settings.email_verification,
+        login_throttle: settings.login_throttle,
     });
     let serve_dir = ServeDir::new("dist");
 
     let app = Router::new()
         .route("/ticket", get(ticket::get))
         .route("/ticket", post(ticket::post))
+        .route("/ticket/export", get(ticket::export))
+        .route("/ticket/:id", put(ticket::put))
+        .route("/ticket/:id", delete(ticket::delete))
+        .route("/ticket/:id/attachment", get(attachment::list))
+        .route(
+            "/ticket/:id/attachment",
+            post(attachment::post).layer(DefaultBodyLimit::max(attachment_body_limit)),
+        )
+        .route("/ticket/:id/split", get(split::get))
+        .route("/ticket/:id/split", put(split::put))
+        .route("/attachment/:id", get(attachment::get))
+        .route("/transfer", post(transfer::post))
+        .route("/transfer/:id", get(transfer::get))
+        .route("/transfer/:id", delete(transfer::delete))
+        .route("/tag", get(tag::get))
+        .route("/tag/suggest", get(tag::suggest))
+        .route("/tag/:id", put(tag::put))
+        .route("/tag/:id", delete(tag::delete))
+        .route("/tag/:id/merge", post(tag::merge))
+        .route("/attachment/:id", delete(attachment::delete))
+        .route("/tickets", get(tickets::get))
+        .route("/tickets", post(tickets::post))
+        .route("/tickets/rows", get(tickets::rows))
+        .route("/tickets/:id", get(tickets::row))
+        .route("/tickets/:id", put(tickets::put))
+        .route("/tickets/:id", delete(tickets::delete))
+        .route("/tickets/:id/edit", get(tickets::edit))
+        .route("/accounting", get(accounting::get))
+        .route("/accounting", post(accounting::post))
+        .route("/accounting/:id", put(accounting::put))
+        .route("/accounting/:id", delete(accounting::delete))
+        .route("/accounting/:id/select", post(accounting::select))
+        .route("/accounting/:id/member", get(member::get))
+        .route("/accounting/:id/member/:user_id", put(member::put))
+        .route("/accounting/:id/member/:user_id", delete(member::delete))
+        .route("/accounting/:id/invitation", post(member::invite))
+        .route("/invitation", get(invitation::get))
+        .route("/invitation/:id/accept", post(invitation::accept))
+        .route("/invitation/:id", delete(invitation::delete))
+        .route("/report/periods", get(report::periods))
+        .route("/report/categories", get(report::categories))
+        .route("/report/tags", get(report::tags))
+        .route("/budget", get(budget::get))
+        .route("/budget", post(budget::post))
+        .route("/budget/:id", put(budget::put))
+        .route("/budget/:id", delete(budget::delete))
+        .route("/recurring", get(recurring::get))
+        .route("/recurring", post(recurring::post))
+        .route("/recurring/:id", put(recurring::put))
+        .route("/recurring/:id", delete(recurring::delete))
+        .route("/import", get(import::get))
+        .route("/import", post(import::post))
+        .route("/import/:id", delete(import::delete))
+        .route("/import/:id/commit", post(import::commit))
+        .route("/currency", get(currency::get))
+        .route("/exchange_rate", get(exchange_rate::get))
+        .route("/exchange_rate", put(exchange_rate::put))
+        .route("/exchange_rate/import", post(exchange_rate::import))
+        .route(
+            "/exchange_rate/:base/:quote/:day",
+            delete(exchange_rate::delete),
+        )
+        .route("/category", get(category::get))
+        .route("/category", post(category::post))
+        .route("/category/:id", put(category::put))
+        .route("/category/:id", delete(category::delete))
+        .route("/session", get(session::get))
+        .route("/session", delete(session::delete_others))
+        .route("/session/:id", delete(session::delete))
+        .route("/sessions", get(session::page))
+        .route("/totp", get(totp::get))
+        .route("/totp/enroll", post(totp::enroll))
+        .route("/totp/confirm", post(totp::confirm))
+        .route("/totp/disable", post(totp::disable))
+        .route("/totp/recovery_codes", post(totp::recovery_codes))
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            email_verification::mw_require_verified,
+        ))
         .route_layer(middleware::from_fn(mw_auth::mw_ctx_require))
         .route("/home", get(home::get))
+        .route("/change_password", get(change_password::get))
+        .route("/change_password", post(change_password::post))
         .route("/logout", post(logout::post))
         .route("/login", get(login::get))
         .route("/login", post(login::post))
+        .route("/login/totp", get(login::totp_page))
+        .route("/login/totp", post(login::totp))
         .route("/signup", post(signup::post))
         .route("/signup", get(signup::get))
+        .route("/forgot", get(forgot::get))
+        .route("/forgot", post(forgot::post))
+        .route("/reset_password", get(reset_password::get))
+        .route("/reset_password", post(reset_password::post))
+        .route("/verify_email", get(verify_email::get))
+        .route("/verify_email/resend", post(verify_email::resend))
         .layer(middleware::from_fn_with_state(
             app_state.clone(),
             mw_auth::mw_ctx_resolver,
@@ -0,0 +1,186 @@
+use crate::model::{
+    member::{require_write, ticket_role},
+    ticket::TicketError,
+};
+use crate::storage::BlobStore;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Debug, serde::Serialize)]
+pub struct Attachment {
+    pub id: Uuid,
+    pub ticket_id: Uuid,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// The types accepted for an upload, found from the first bytes of the file so a renamed
+/// file can't pass for another type.
+pub fn sniff_content_type(data: &[u8]) -> Option<&'static str> {
+    match data {
+        [0xFF, 0xD8, 0xFF, ..] => Some("image/jpeg"),
+        [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n', ..] => Some("image/png"),
+        [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => Some("image/gif"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("image/webp"),
+        [_, _, _, _, b'f', b't', b'y', b'p', brand @ ..]
+            if [&b"heic"[..], b"heix", b"mif1", b"msf1"]
+                .iter()
+                .any(|heic| brand.starts_with(heic)) =>
+        {
+            Some("image/heic")
+        }
+        [b'%', b'P', b'D', b'F', b'-', ..] => Some("application/pdf"),
+        _ => None,
+    }
+}
+
+/// Keep the last component of an uploaded file name, without anything that could break a
+/// header.
+pub fn clean_file_name(file_name: Option<&str>) -> String {
+    let name: String = file_name
+        .unwrap_or_default()
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control() && *c != '"')
+        .take(255)
+        .collect();
+    match name.trim() {
+        "" => "attachment".to_string(),
+        name => name.to_string(),
+    }
+}
+
+pub async fn list(
+    pool: &PgPool,
+    user_id: Uuid,
+    ticket_id: Uuid,
+) -> Result<Vec<Attachment>, TicketError> {
+    let attachments = sqlx::query_as!(
+        Attachment,
+        r#"SELECT
+            at.id,
+            at.movement_id as ticket_id,
+            at.file_name,
+            at.content_type,
+            at.size,
+            at.created_at
+        FROM tbl_attachment at
+          INNER JOIN accounting_movement_tbl amt ON amt.id = at.movement_id
+          INNER JOIN tbl_accounting_member tam ON tam.accounting_id = amt.accounting_id
+        WHERE
+            amt.id = $1 AND tam.user_id = $2
+        ORDER BY at.created_at"#,
+        ticket_id,
+        user_id,
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(attachments)
+}
+
+/// Record a file already written to the blob store under `storage_key`.
+pub async fn create(
+    pool: &PgPool,
+    user_id: Uuid,
+    ticket_id: Uuid,
+    file_name: &str,
+    content_type: &str,
+    size: i64,
+    storage_key: &str,
+) -> Result<Uuid, TicketError> {
+    require_write(ticket_role(pool, user_id, ticket_id).await?)?;
+    let row = sqlx::query!(
+        r#"INSERT INTO tbl_attachment(movement_id, file_name, content_type, size, storage_key)
+        SELECT amt.id, $3, $4, $5, $6
+        FROM accounting_movement_tbl amt
+          INNER JOIN tbl_accounting_member tam ON tam.accounting_id = amt.accounting_id
+        WHERE
+            amt.id = $1 AND tam.user_id = $2 AND tam.role <> 'viewer'
+        RETURNING id"#,
+        ticket_id,
+        user_id,
+        file_name,
+        content_type,
+        size,
+        storage_key,
+    )
+    .fetch_optional(pool)
+    .await?;
+    row.map(|row| row.id).ok_or(TicketError::NotFound)
+}
+
+/// The attachment and the key of its content.
+pub async fn get(
+    pool: &PgPool,
+    user_id: Uuid,
+    attachment_id: Uuid,
+) -> Result<(Attachment, String), TicketError> {
+    let row = sqlx::query!(
+        r#"SELECT
+            at.id,
+            at.movement_id,
+            at.file_name,
+            at.content_type,
+            at.size,
+            at.created_at,
+            at.storage_key
+        FROM tbl_attachment at
+          INNER JOIN accounting_movement_tbl amt ON amt.id = at.movement_id
+          INNER JOIN tbl_accounting_member tam ON tam.accounting_id = amt.accounting_id
+        WHERE
+            at.id = $1 AND tam.user_id = $2"#,
+        attachment_id,
+        user_id,
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(TicketError::NotFound)?;
+    Ok((
+        Attachment {
+            id: row.id,
+            ticket_id: row.movement_id,
+            file_name: row.file_name,
+            content_type: row.content_type,
+            size: row.size,
+            created_at: row.created_at,
+        },
+        row.storage_key,
+    ))
+}
+
+/// Returns the key of the content, which is left to the caller to remove from the blob store.
+pub async fn delete(
+    pool: &PgPool,
+    user_id: Uuid,
+    attachment_id: Uuid,
+) -> Result<String, TicketError> {
+    let (attachment, _) = get(pool, user_id, attachment_id).await?;
+    require_write(ticket_role(pool, user_id, attachment.ticket_id).await?)?;
+    let row = sqlx::query!(
+        r#"DELETE FROM tbl_attachment at
+        USING accounting_movement_tbl amt, tbl_accounting_member tam
+        WHERE
+            amt.id = at.movement_id AND tam.accounting_id = amt.accounting_id
+            AND at.id = $1 AND tam.user_id = $2 AND tam.role <> 'viewer'
+        RETURNING at.storage_key"#,
+        attachment_id,
+        user_id,
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(TicketError::NotFound)?;
+    Ok(row.storage_key)
+}
+
+/// Remove the content of deleted attachments, a failure only leaves an orphan file behind.
+pub async fn remove_blobs(store: &dyn BlobStore, storage_keys: Vec<String>) {
+    for key in storage_keys {
+        if let Err(e) = store.delete(&key).await {
+            tracing::error!("Failed removing attachment {}: {}", key, e);
+        }
+    }
+}
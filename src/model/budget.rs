@@ -0,0 +1,105 @@
+use sqlx::{types::Decimal, PgPool};
+use uuid::Uuid;
+
+#[derive(Clone, Copy, Debug, PartialEq, sqlx::Type, serde::Deserialize, serde::Serialize)]
+#[sqlx(type_name = "BUDGET_PERIOD", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    Month,
+    Year,
+}
+
+/// A budget with what has been spent in its category, children included, since the start of
+/// the current period. Spending in another currency is converted with the rates of its book's
+/// owner as in reports, lines without a known rate are left out of `spent` and counted in
+/// `unconverted`.
+#[derive(Debug, serde::Serialize)]
+pub struct BudgetStatus {
+    pub id: Uuid,
+    pub type_id: Uuid,
+    pub type_name: String,
+    pub accounting_id: Option<Uuid>,
+    pub period: BudgetPeriod,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
+    pub currency: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub spent: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub remaining: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub percent_used: Decimal,
+    pub over_budget: bool,
+    pub unconverted: i64,
+}
+
+pub async fn statuses(pool: &PgPool, user_id: Uuid) -> Result<Vec<BudgetStatus>, sqlx::Error> {
+    sqlx::query_as!(
+        BudgetStatus,
+        r#"SELECT
+            b.id,
+            b.type_id,
+            tt.name as type_name,
+            b.accounting_id,
+            b.period as "period: BudgetPeriod",
+            ROUND(b.amount, tc.minor_units) as "amount!",
+            b.currency,
+            ROUND(s.spent, tc.minor_units) as "spent!",
+            ROUND(b.amount - s.spent, tc.minor_units) as "remaining!",
+            ROUND(s.spent * 100 / b.amount, 2) as "percent_used!",
+            s.spent > b.amount as "over_budget!",
+            s.unconverted as "unconverted!"
+        FROM tbl_budget b
+          INNER JOIN tbl_type tt ON tt.id = b.type_id
+          INNER JOIN tbl_currency tc ON tc.code = b.currency
+          CROSS JOIN LATERAL (
+            SELECT
+                COALESCE(SUM(line.amount), 0) as spent,
+                COUNT(*) FILTER (WHERE line.amount IS NULL) as unconverted
+            FROM (
+                SELECT
+                    CASE
+                        WHEN amt.currency = b.currency THEN amt.amount
+                        ELSE ROUND(amt.amount * fx.rate, tc.minor_units)
+                    END as amount
+                FROM movement_line amt
+                  INNER JOIN tbl_accounting ta ON ta.id = amt.accounting_id
+                  INNER JOIN tbl_accounting_member tam ON tam.accounting_id = amt.accounting_id
+                  LEFT JOIN LATERAL (
+                    SELECT CASE WHEN er.base = amt.currency THEN er.rate ELSE 1 / er.rate END as rate
+                    FROM tbl_exchange_rate er
+                    WHERE
+                        er.user_id = ta.user_id
+                        AND (
+                            (er.base = amt.currency AND er.quote = b.currency)
+                            OR (er.base = b.currency AND er.quote = amt.currency)
+                        )
+                        AND er.day <= (amt.created_at AT TIME ZONE 'UTC')::date
+                    ORDER BY er.day DESC, er.base = amt.currency DESC
+                    LIMIT 1
+                  ) fx ON amt.currency <> b.currency
+                WHERE
+                    tam.user_id = b.user_id
+                    AND (b.accounting_id IS NULL OR tam.accounting_id = b.accounting_id)
+                    AND amt.direction = 'out'
+                    AND amt.transfer_id IS NULL
+                    AND amt.created_at >= date_trunc(b.period::text, NOW(), 'UTC')
+                    AND amt.created_at < date_trunc(b.period::text, NOW(), 'UTC') + ('1 ' || b.period::text)::interval
+                    AND amt.type_id IN (
+                        WITH RECURSIVE subtree AS (
+                            SELECT b.type_id as id
+                            UNION
+                            SELECT child.id FROM tbl_type child INNER JOIN subtree ON child.parent_id = subtree.id
+                        )
+                        SELECT id FROM subtree
+                    )
+            ) line
+          ) s
+        WHERE
+            b.user_id = $1
+        ORDER BY tt.name, b.period"#,
+        user_id,
+    )
+    .fetch_all(pool)
+    .await
+}
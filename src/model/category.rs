@@ -0,0 +1,32 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub struct CategoryPath {
+    pub id: Uuid,
+    /// Names from the root category down to this one, joined by " / ".
+    pub path: String,
+}
+
+/// Every category the user can file tickets under, sorted by path.
+pub async fn usable_paths(pool: &PgPool, user_id: Uuid) -> Result<Vec<CategoryPath>, sqlx::Error> {
+    sqlx::query_as!(
+        CategoryPath,
+        r#"WITH RECURSIVE tree AS (
+            SELECT id, name AS path
+            FROM tbl_type
+            WHERE
+                parent_id IS NULL AND (user_id IS NULL OR user_id = $1) AND archived_at IS NULL
+            UNION ALL
+            SELECT tt.id, tree.path || ' / ' || tt.name
+            FROM tbl_type tt
+              INNER JOIN tree ON tt.parent_id = tree.id
+            WHERE
+                (tt.user_id IS NULL OR tt.user_id = $1) AND tt.archived_at IS NULL
+        )
+        SELECT id as "id!", path as "path!" FROM tree ORDER BY path"#,
+        user_id,
+    )
+    .fetch_all(pool)
+    .await
+}
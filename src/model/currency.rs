@@ -0,0 +1,224 @@
+use crate::model::import::decode_text;
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use chrono::NaiveDate;
+use sqlx::{types::Decimal, PgPool};
+use std::collections::{BTreeMap, HashSet};
+use std::str::FromStr;
+use uuid::Uuid;
+
+#[derive(Debug, serde::Serialize)]
+pub struct Currency {
+    pub code: String,
+    /// Decimals an amount in this currency can have, 0 for JPY, 3 for KWD.
+    pub minor_units: i16,
+}
+
+/// 1 `base` is worth `rate` `quote` from `day` until the next rate of the pair.
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+pub struct ExchangeRate {
+    pub base: String,
+    pub quote: String,
+    pub day: NaiveDate,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub rate: Decimal,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum RateError {
+    #[error("{0}")]
+    Invalid(String),
+    #[error(transparent)]
+    UnexpectedError(#[from] sqlx::Error),
+}
+
+impl IntoResponse for RateError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Invalid(message) => (StatusCode::BAD_REQUEST, message).into_response(),
+            Self::UnexpectedError(e) => {
+                tracing::error!("Failed exchange rate operation: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        }
+    }
+}
+
+/// Currency codes are stored upper case.
+pub fn normalize_code(code: &str) -> String {
+    code.trim().to_ascii_uppercase()
+}
+
+pub async fn list(pool: &PgPool) -> Result<Vec<Currency>, sqlx::Error> {
+    sqlx::query_as!(
+        Currency,
+        "SELECT code, minor_units FROM tbl_currency ORDER BY code"
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Decimals of a currency, `None` when the code is unknown.
+pub async fn minor_units(pool: &PgPool, code: &str) -> Result<Option<u32>, sqlx::Error> {
+    let row = sqlx::query!(
+        "SELECT minor_units FROM tbl_currency WHERE code = $1",
+        normalize_code(code),
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|row| row.minor_units as u32))
+}
+
+pub async fn rates(pool: &PgPool, user_id: Uuid) -> Result<Vec<ExchangeRate>, sqlx::Error> {
+    sqlx::query_as!(
+        ExchangeRate,
+        r#"SELECT base, quote, day, rate
+        FROM tbl_exchange_rate
+        WHERE user_id = $1
+        ORDER BY base, quote, day DESC"#,
+        user_id,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+fn check_rate(rate: &ExchangeRate, currencies: &HashSet<String>) -> Result<(), &'static str> {
+    if !currencies.contains(&rate.base) || !currencies.contains(&rate.quote) {
+        return Err("unknown currency");
+    }
+    if rate.base == rate.quote {
+        return Err("base and quote must be different currencies");
+    }
+    if rate.rate.is_sign_negative() || rate.rate.is_zero() {
+        return Err("rate must be greater than zero");
+    }
+    Ok(())
+}
+
+async fn codes(pool: &PgPool) -> Result<HashSet<String>, sqlx::Error> {
+    Ok(list(pool)
+        .await?
+        .into_iter()
+        .map(|currency| currency.code)
+        .collect())
+}
+
+/// Add rates, or replace the ones already known for the same pair and day.
+async fn upsert(pool: &PgPool, user_id: Uuid, rates: &[ExchangeRate]) -> Result<(), sqlx::Error> {
+    let (mut bases, mut quotes, mut days, mut values) = (vec![], vec![], vec![], vec![]);
+    for rate in rates {
+        bases.push(rate.base.clone());
+        quotes.push(rate.quote.clone());
+        days.push(rate.day);
+        values.push(rate.rate);
+    }
+    sqlx::query!(
+        r#"INSERT INTO tbl_exchange_rate(user_id, base, quote, day, rate)
+        SELECT $1, * FROM UNNEST($2::bpchar[], $3::bpchar[], $4::date[], $5::numeric[])
+        ON CONFLICT (user_id, base, quote, day) DO UPDATE SET rate = EXCLUDED.rate"#,
+        user_id,
+        &bases,
+        &quotes,
+        &days,
+        &values,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn save_rate(
+    pool: &PgPool,
+    user_id: Uuid,
+    mut rate: ExchangeRate,
+) -> Result<(), RateError> {
+    rate.base = normalize_code(&rate.base);
+    rate.quote = normalize_code(&rate.quote);
+    check_rate(&rate, &codes(pool).await?).map_err(|e| RateError::Invalid(e.to_string()))?;
+    upsert(pool, user_id, &[rate]).await?;
+    Ok(())
+}
+
+/// Returns whether the rate existed.
+pub async fn delete_rate(
+    pool: &PgPool,
+    user_id: Uuid,
+    base: &str,
+    quote: &str,
+    day: NaiveDate,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        "DELETE FROM tbl_exchange_rate WHERE user_id = $1 AND base = $2 AND quote = $3 AND day = $4",
+        user_id,
+        normalize_code(base),
+        normalize_code(quote),
+        day,
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Read a CSV of `date,base,quote,rate` lines with `YYYY-MM-DD` dates, a first line starting with
+/// `date` is taken as a header.
+fn parse_rates(data: &[u8], currencies: &HashSet<String>) -> Result<Vec<ExchangeRate>, String> {
+    let text = decode_text(data);
+    let mut reader = ::csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(::csv::Trim::All)
+        .from_reader(text.as_bytes());
+    // the last rate of a file wins, as it would have on separate imports
+    let mut rates = BTreeMap::new();
+    for (index, record) in reader.records().enumerate() {
+        let line = record
+            .as_ref()
+            .ok()
+            .and_then(|record| record.position())
+            .map_or(index as u64 + 1, |position| position.line());
+        let record = record.map_err(|_| format!("line {line}: unreadable row"))?;
+        if record.iter().all(str::is_empty) {
+            continue;
+        }
+        if index == 0
+            && record
+                .get(0)
+                .is_some_and(|field| field.eq_ignore_ascii_case("date"))
+        {
+            continue;
+        }
+        let field = |position: usize, name: &str| {
+            record
+                .get(position)
+                .filter(|value| !value.is_empty())
+                .ok_or_else(|| format!("line {line}: missing {name}"))
+        };
+        let day = field(0, "date")?;
+        let rate = ExchangeRate {
+            day: NaiveDate::parse_from_str(day, "%Y-%m-%d")
+                .map_err(|_| format!("line {line}: invalid date '{day}'"))?,
+            base: normalize_code(field(1, "base")?),
+            quote: normalize_code(field(2, "quote")?),
+            rate: {
+                let rate = field(3, "rate")?;
+                Decimal::from_str(rate)
+                    .map_err(|_| format!("line {line}: invalid rate '{rate}'"))?
+            },
+        };
+        check_rate(&rate, currencies).map_err(|e| format!("line {line}: {e}"))?;
+        rates.insert((rate.base.clone(), rate.quote.clone(), rate.day), rate);
+    }
+    Ok(rates.into_values().collect())
+}
+
+/// Import a CSV of rates, either all of them are saved or none. Returns how many were saved.
+pub async fn import_rates(pool: &PgPool, user_id: Uuid, data: &[u8]) -> Result<usize, RateError> {
+    let rates = parse_rates(data, &codes(pool).await?).map_err(RateError::Invalid)?;
+    if rates.is_empty() {
+        return Err(RateError::Invalid("no rate found".to_string()));
+    }
+    upsert(pool, user_id, &rates).await?;
+    Ok(rates.len())
+}
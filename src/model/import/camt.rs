@@ -0,0 +1,163 @@
+//! ISO 20022 bank to customer statements (camt.053) and debit/credit notifications (camt.054).
+//!
+//! Elements are looked up by local name so every version of the schema is read the same way.
+use super::{Entry, Fingerprints, ParsedRow};
+use crate::model::direction::TicketDirection;
+use chrono::{DateTime, NaiveDate, NaiveTime};
+use roxmltree::{Document, Node};
+use sqlx::types::Decimal;
+use std::str::FromStr;
+
+pub fn parse(data: &[u8]) -> Result<Vec<ParsedRow>, &'static str> {
+    let text = std::str::from_utf8(data).map_err(|_| "the statement must be UTF-8")?;
+    let document = Document::parse(text).map_err(|_| "not a valid XML file")?;
+    let root = document.root_element();
+    if root.tag_name().name() != "Document" {
+        return Err("not a camt statement");
+    }
+    let reports: Vec<Node> = root
+        .descendants()
+        .filter(|node| matches!(node.tag_name().name(), "Stmt" | "Ntfctn"))
+        .collect();
+    if reports.is_empty() {
+        return Err("not a camt.053 or camt.054 statement");
+    }
+    let mut fingerprints = Fingerprints::default();
+    let mut rows = Vec::new();
+    for report in reports {
+        let account = child(report, "Acct")
+            .and_then(|account| child(account, "Id"))
+            .and_then(|id| child(id, "IBAN").or_else(|| path(id, &["Othr", "Id"])))
+            .and_then(|id| id.text())
+            .map(str::trim);
+        for entry in report.children().filter(|node| is(*node, "Ntry")) {
+            let line = document.text_pos_at(entry.range().start).row as i32;
+            let parsed = read_entry(entry).map(|(mut parsed, reference)| {
+                parsed.external_id = match (account, reference) {
+                    (Some(account), Some(reference)) => format!("camt:{account}:{reference}"),
+                    (None, Some(reference)) => format!("camt:{reference}"),
+                    (_, None) => fingerprints.external_id("camt", &parsed),
+                };
+                parsed
+            });
+            rows.push(ParsedRow {
+                line,
+                entry: parsed,
+            });
+        }
+    }
+    Ok(rows)
+}
+
+fn is(node: Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| is(*child, name))
+}
+
+fn path<'a, 'input>(node: Node<'a, 'input>, names: &[&str]) -> Option<Node<'a, 'input>> {
+    names.iter().try_fold(node, |node, name| child(node, name))
+}
+
+fn text<'a>(node: Node<'a, '_>, names: &[&str]) -> Option<&'a str> {
+    path(node, names)
+        .and_then(|node| node.text())
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+}
+
+/// The entry and its reference, if the bank gave one.
+fn read_entry(entry: Node) -> Result<(Entry, Option<String>), String> {
+    // `<Sts>BOOK</Sts>` up to version 7, `<Sts><Cd>BOOK</Cd></Sts>` afterwards
+    let status = text(entry, &["Sts"]).or_else(|| text(entry, &["Sts", "Cd"]));
+    if let Some(status) = status.filter(|status| *status != "BOOK") {
+        return Err(format!("entry is not booked ({status})"));
+    }
+    let amount_node = child(entry, "Amt").ok_or("missing Amt")?;
+    let amount = text(entry, &["Amt"]).ok_or("missing Amt")?;
+    let amount = Decimal::from_str(amount).map_err(|_| format!("invalid amount '{amount}'"))?;
+    let direction = match text(entry, &["CdtDbtInd"]) {
+        Some("CRDT") => TicketDirection::In,
+        Some("DBIT") => TicketDirection::Out,
+        Some(indicator) => return Err(format!("invalid CdtDbtInd '{indicator}'")),
+        None => return Err("missing CdtDbtInd".to_string()),
+    };
+    let date = ["BookgDt", "ValDt"]
+        .iter()
+        .find_map(|name| text(entry, &[name, "Dt"]).or_else(|| text(entry, &[name, "DtTm"])))
+        .ok_or("missing BookgDt")?;
+    let created_at = parse_date(date)
+        .ok_or_else(|| format!("invalid date '{date}'"))?
+        .and_time(NaiveTime::MIN)
+        .and_utc();
+    let transaction = path(entry, &["NtryDtls", "TxDtls"]);
+    let reference = text(entry, &["NtryRef"])
+        .or_else(|| text(entry, &["AcctSvcrRef"]))
+        .or_else(|| transaction.and_then(|tx| text(tx, &["Refs", "AcctSvcrRef"])))
+        .map(str::to_string);
+    Ok((
+        Entry {
+            created_at,
+            direction: direction.clone(),
+            amount,
+            description: description(entry, transaction, &direction),
+            category: None,
+            currency: amount_node.attribute("Ccy").map(str::to_string),
+            external_id: String::new(),
+        },
+        reference,
+    ))
+}
+
+/// The counterparty followed by the remittance information.
+fn description(entry: Node, transaction: Option<Node>, direction: &TicketDirection) -> String {
+    let counterparty = transaction.and_then(|tx| {
+        let party = match direction {
+            TicketDirection::Out => "Cdtr",
+            TicketDirection::In => "Dbtr",
+        };
+        // `<Cdtr><Nm>` up to version 7, `<Cdtr><Pty><Nm>` afterwards
+        text(tx, &["RltdPties", party, "Nm"])
+            .or_else(|| text(tx, &["RltdPties", party, "Pty", "Nm"]))
+    });
+    let remittance: Vec<&str> = transaction
+        .and_then(|tx| child(tx, "RmtInf"))
+        .map(|info| {
+            info.children()
+                .filter(|node| is(*node, "Ustrd"))
+                .filter_map(|node| node.text())
+                .map(str::trim)
+                .collect()
+        })
+        .unwrap_or_default();
+    let remittance = if remittance.is_empty() {
+        transaction
+            .and_then(|tx| text(tx, &["RmtInf", "Strd", "CdtrRefInf", "Ref"]))
+            .or_else(|| text(entry, &["AddtlNtryInf"]))
+            .unwrap_or_default()
+            .to_string()
+    } else {
+        remittance.join(" ")
+    };
+    match counterparty {
+        Some(counterparty) if !remittance.is_empty() => format!("{counterparty} - {remittance}"),
+        Some(counterparty) => counterparty.to_string(),
+        None => remittance,
+    }
+}
+
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .ok()
+        .or_else(|| {
+            DateTime::parse_from_rfc3339(date)
+                .ok()
+                .map(|date| date.date_naive())
+        })
+        .or_else(|| {
+            date.get(..10)
+                .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+        })
+}
@@ -0,0 +1,204 @@
+use super::{decode_text, Entry, Fingerprints, ParsedRow};
+use crate::model::direction::TicketDirection;
+use chrono::{NaiveDate, NaiveTime};
+use serde_aux::field_attributes::deserialize_bool_from_anything;
+use sqlx::types::Decimal;
+use std::str::FromStr;
+
+/// How the direction of a row is found.
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AmountSign {
+    /// Negative amounts are expenses.
+    #[default]
+    NegativeIsOut,
+    /// Negative amounts are incomes, as on credit card statements.
+    NegativeIsIn,
+    /// The amount is read unsigned and `direction_column` tells incomes from expenses.
+    DirectionColumn,
+}
+
+/// Where the fields of a movement are in a CSV file.
+///
+/// Columns are given by header name, or by 1-based position.
+#[derive(Debug, serde::Deserialize)]
+pub struct CsvMapping {
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+    #[serde(default, deserialize_with = "deserialize_bool_from_anything")]
+    pub has_header: bool,
+    pub date_column: String,
+    /// A chrono format string, like `%d/%m/%Y`.
+    #[serde(default = "default_date_format")]
+    pub date_format: String,
+    pub amount_column: String,
+    #[serde(default)]
+    pub amount_sign: AmountSign,
+    pub direction_column: Option<String>,
+    pub description_column: Option<String>,
+    pub category_column: Option<String>,
+    /// Amounts are written like `1.234,56`.
+    #[serde(default, deserialize_with = "deserialize_bool_from_anything")]
+    pub decimal_comma: bool,
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+fn default_date_format() -> String {
+    "%Y-%m-%d".to_string()
+}
+
+struct Columns {
+    date: usize,
+    amount: usize,
+    direction: Option<usize>,
+    description: Option<usize>,
+    category: Option<usize>,
+}
+
+fn find_column(
+    headers: Option<&::csv::StringRecord>,
+    column: &str,
+    missing: &'static str,
+) -> Result<usize, &'static str> {
+    if let Ok(position) = column.trim().parse::<usize>() {
+        return position.checked_sub(1).ok_or(missing);
+    }
+    headers
+        .and_then(|headers| {
+            headers
+                .iter()
+                .position(|header| header.trim().eq_ignore_ascii_case(column.trim()))
+        })
+        .ok_or(missing)
+}
+
+/// Read a CSV statement, the error is returned when the mapping doesn't fit the file.
+pub fn parse(data: &[u8], mapping: &CsvMapping) -> Result<Vec<ParsedRow>, &'static str> {
+    if !mapping.delimiter.is_ascii() {
+        return Err("the delimiter must be an ASCII character");
+    }
+    if mapping.amount_sign == AmountSign::DirectionColumn && mapping.direction_column.is_none() {
+        return Err("a direction column is needed to read unsigned amounts");
+    }
+    // the reader counts lines wrong after a header ending in CRLF
+    let text = decode_text(data).replace("\r\n", "\n");
+    let mut reader = ::csv::ReaderBuilder::new()
+        .delimiter(mapping.delimiter as u8)
+        .has_headers(mapping.has_header)
+        .flexible(true)
+        .trim(::csv::Trim::All)
+        .from_reader(text.as_bytes());
+    let headers = if mapping.has_header {
+        Some(
+            reader
+                .headers()
+                .map_err(|_| "can't read the header")?
+                .clone(),
+        )
+    } else {
+        None
+    };
+    let headers = headers.as_ref();
+    let optional_column = |column: &Option<String>, missing| {
+        column
+            .as_deref()
+            .map(|column| find_column(headers, column, missing))
+            .transpose()
+    };
+    let columns = Columns {
+        date: find_column(headers, &mapping.date_column, "unknown date column")?,
+        amount: find_column(headers, &mapping.amount_column, "unknown amount column")?,
+        direction: optional_column(&mapping.direction_column, "unknown direction column")?,
+        description: optional_column(&mapping.description_column, "unknown description column")?,
+        category: optional_column(&mapping.category_column, "unknown category column")?,
+    };
+
+    let mut fingerprints = Fingerprints::default();
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let position = match &record {
+            Ok(record) => record.position(),
+            Err(error) => error.position(),
+        };
+        let line = position.map_or(rows.len() as u64 + 1, |position| position.line()) as i32;
+        let entry = match record {
+            Ok(record) => parse_record(&record, &columns, mapping).map(|mut entry| {
+                entry.external_id = fingerprints.external_id("csv", &entry);
+                entry
+            }),
+            Err(_) => Err("unreadable row".to_string()),
+        };
+        rows.push(ParsedRow { line, entry });
+    }
+    Ok(rows)
+}
+
+fn parse_record(
+    record: &::csv::StringRecord,
+    columns: &Columns,
+    mapping: &CsvMapping,
+) -> Result<Entry, String> {
+    let field = |column: usize, name: &str| {
+        record
+            .get(column)
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| format!("missing {name}"))
+    };
+    let date = field(columns.date, "date")?;
+    let created_at = NaiveDate::parse_from_str(date, &mapping.date_format)
+        .map_err(|_| format!("invalid date '{date}'"))?
+        .and_time(NaiveTime::MIN)
+        .and_utc();
+    let amount = field(columns.amount, "amount")?;
+    let amount = parse_amount(amount, mapping.decimal_comma)
+        .ok_or_else(|| format!("invalid amount '{amount}'"))?;
+    let direction = match mapping.amount_sign {
+        AmountSign::NegativeIsOut if amount.is_sign_negative() => TicketDirection::Out,
+        AmountSign::NegativeIsOut => TicketDirection::In,
+        AmountSign::NegativeIsIn if amount.is_sign_negative() => TicketDirection::In,
+        AmountSign::NegativeIsIn => TicketDirection::Out,
+        AmountSign::DirectionColumn => {
+            let direction = field(columns.direction.unwrap_or(usize::MAX), "direction")?;
+            parse_direction(direction).ok_or_else(|| format!("invalid direction '{direction}'"))?
+        }
+    };
+    let description = columns
+        .description
+        .and_then(|column| record.get(column))
+        .unwrap_or_default()
+        .to_string();
+    let category = columns
+        .category
+        .and_then(|column| record.get(column))
+        .filter(|category| !category.is_empty())
+        .map(str::to_string);
+    Ok(Entry {
+        created_at,
+        direction,
+        amount: amount.abs(),
+        description,
+        category,
+        currency: None,
+        external_id: String::new(),
+    })
+}
+
+fn parse_amount(amount: &str, decimal_comma: bool) -> Option<Decimal> {
+    let amount: String = amount
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != if decimal_comma { '.' } else { ',' })
+        .map(|c| if c == ',' { '.' } else { c })
+        .collect();
+    Decimal::from_str(amount.strip_prefix('+').unwrap_or(&amount)).ok()
+}
+
+fn parse_direction(direction: &str) -> Option<TicketDirection> {
+    match direction.to_lowercase().as_str() {
+        "in" | "credit" | "cr" | "c" | "+" => Some(TicketDirection::In),
+        "out" | "debit" | "dr" | "d" | "-" => Some(TicketDirection::Out),
+        _ => None,
+    }
+}
@@ -0,0 +1,374 @@
+//! Bank statement import: statements are parsed into rows, staged for the user to preview,
+//! and only written to `accounting_movement_tbl` once the user commits them.
+pub mod camt;
+pub mod csv;
+pub mod ofx;
+pub mod qif;
+
+use crate::model::{
+    category, currency,
+    direction::TicketDirection,
+    member::{book_role, forbid_viewer},
+    ticket::{
+        validation::{is_usable_type, validate_amount},
+        TicketError,
+    },
+};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::{types::Decimal, PgPool, Postgres, QueryBuilder};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Larger statements have to be split by the user.
+pub const MAX_ROWS: usize = 5000;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementFormat {
+    #[default]
+    Csv,
+    /// OFX and QFX, SGML or XML.
+    Ofx,
+    Qif,
+    /// ISO 20022 camt.053 and camt.054.
+    Camt,
+}
+
+/// A movement read from a statement.
+#[derive(Debug)]
+pub struct Entry {
+    pub created_at: DateTime<Utc>,
+    pub direction: TicketDirection,
+    pub amount: Decimal,
+    pub description: String,
+    /// Category path or name, the default category of the import is used when missing.
+    pub category: Option<String>,
+    /// Currency code given by the statement, the currency of the book is used when missing.
+    pub currency: Option<String>,
+    /// Identifies the movement in its source, a row with an `external_id` already in the book
+    /// is a duplicate.
+    pub external_id: String,
+}
+
+/// A line of a statement, or the reason why it can't be read.
+#[derive(Debug)]
+pub struct ParsedRow {
+    pub line: i32,
+    pub entry: Result<Entry, String>,
+}
+
+/// A row waiting to be committed.
+#[derive(Debug)]
+pub struct StagedRow {
+    pub line: i32,
+    pub error: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub direction: Option<TicketDirection>,
+    pub amount: Option<Decimal>,
+    pub currency: Option<String>,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub duplicate: bool,
+}
+
+/// Statements are mostly UTF-8, older ones are Latin-1.
+pub(crate) fn decode_text(data: &[u8]) -> String {
+    match std::str::from_utf8(data) {
+        Ok(text) => text.trim_start_matches('\u{feff}').to_string(),
+        Err(_) => data.iter().map(|&byte| byte as char).collect(),
+    }
+}
+
+/// `external_id` of the rows of formats without transaction ids.
+///
+/// Identical rows are legit (two coffees on the same day), so the fingerprint of a row also
+/// counts how many times it was already seen in the statement.
+#[derive(Default)]
+struct Fingerprints(HashMap<String, u32>);
+
+impl Fingerprints {
+    fn external_id(&mut self, prefix: &str, entry: &Entry) -> String {
+        let key = format!(
+            "{}|{:?}|{}|{}",
+            entry.created_at.date_naive(),
+            entry.direction,
+            entry.amount.normalize(),
+            entry.description,
+        );
+        let count = self.0.entry(key.clone()).or_default();
+        *count += 1;
+        format!(
+            "{prefix}:{:x}",
+            Sha256::digest(format!("{key}#{count}").as_bytes())
+        )
+    }
+}
+
+/// Resolve category names the way the user sees them: full paths first, then bare names.
+struct Categories(HashMap<String, Uuid>);
+
+impl Categories {
+    async fn load(pool: &PgPool, user_id: Uuid) -> Result<Self, sqlx::Error> {
+        let paths = category::usable_paths(pool, user_id).await?;
+        let mut categories = HashMap::new();
+        for category in &paths {
+            if let Some(name) = category.path.rsplit(" / ").next() {
+                categories.entry(name.to_lowercase()).or_insert(category.id);
+            }
+        }
+        for category in paths {
+            categories.insert(category.path.to_lowercase(), category.id);
+        }
+        Ok(Self(categories))
+    }
+
+    fn find(&self, name: &str) -> Option<Uuid> {
+        self.0.get(&name.trim().to_lowercase()).copied()
+    }
+}
+
+/// Stage the rows of a statement for `accounting_id`, returns the id of the import.
+///
+/// Rows that can't be imported keep their error, rows already in the book or repeated in the
+/// statement are flagged as duplicates.
+pub async fn stage(
+    pool: &PgPool,
+    user_id: Uuid,
+    accounting_id: Uuid,
+    type_id: Uuid,
+    rows: Vec<ParsedRow>,
+) -> Result<Uuid, TicketError> {
+    if rows.is_empty() {
+        return Err(TicketError::Invalid("the statement has no rows"));
+    }
+    if rows.len() > MAX_ROWS {
+        return Err(TicketError::Invalid(
+            "the statement has more than 5000 rows",
+        ));
+    }
+    if !is_usable_type(pool, user_id, type_id, None).await? {
+        return Err(TicketError::Invalid("unknown type_id"));
+    }
+    forbid_viewer(book_role(pool, user_id, Some(accounting_id)).await?)?;
+    let book_currency = sqlx::query!(
+        r#"SELECT ta.currency
+        FROM tbl_accounting ta
+          INNER JOIN tbl_accounting_member tam ON tam.accounting_id = ta.id
+        WHERE ta.id = $1 AND tam.user_id = $2"#,
+        accounting_id,
+        user_id,
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(TicketError::Invalid("unknown accounting_id"))?
+    .currency;
+    let minor_units: HashMap<String, u32> = currency::list(pool)
+        .await?
+        .into_iter()
+        .map(|currency| (currency.code, currency.minor_units as u32))
+        .collect();
+    let categories = Categories::load(pool, user_id).await?;
+    let rows = rows
+        .into_iter()
+        .map(|row| {
+            let entry = row.entry.and_then(|mut entry| {
+                let code = match &entry.currency {
+                    Some(code) => currency::normalize_code(code),
+                    None => book_currency.clone(),
+                };
+                let minor_units = *minor_units
+                    .get(&code)
+                    .ok_or_else(|| format!("unknown currency '{code}'"))?;
+                validate_amount(&entry.amount, minor_units).map_err(str::to_string)?;
+                entry.amount.rescale(minor_units);
+                entry.currency = Some(code);
+                let type_id = match &entry.category {
+                    Some(name) => categories
+                        .find(name)
+                        .ok_or_else(|| format!("unknown category '{name}'"))?,
+                    None => type_id,
+                };
+                Ok((entry, type_id))
+            });
+            (row.line, entry)
+        })
+        .collect::<Vec<_>>();
+
+    let mut transaction = pool.begin().await?;
+    sqlx::query!(
+        "DELETE FROM tbl_import WHERE user_id = $1 AND created_at < NOW() - interval '1 day'",
+        user_id,
+    )
+    .execute(&mut *transaction)
+    .await?;
+    let import_id = sqlx::query!(
+        r#"INSERT INTO tbl_import(user_id, accounting_id)
+        SELECT tam.user_id, tam.accounting_id
+        FROM tbl_accounting_member tam
+        WHERE tam.accounting_id = $1 AND tam.user_id = $2 AND tam.role <> 'viewer'
+        RETURNING id"#,
+        accounting_id,
+        user_id,
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    .ok_or(TicketError::Invalid("unknown accounting_id"))?
+    .id;
+    // stay well below the 65535 bind parameters of a statement
+    for chunk in rows.chunks(1000) {
+        let mut builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO tbl_import_row(import_id, line, error, created_at, direction, amount, currency, description, type_id, external_id) ",
+        );
+        builder.push_values(chunk, |mut values, (line, row)| {
+            values.push_bind(import_id).push_bind(line);
+            match row {
+                Ok((entry, type_id)) => values
+                    .push_bind(None::<String>)
+                    .push_bind(entry.created_at)
+                    .push_bind(entry.direction.clone())
+                    .push_bind(entry.amount)
+                    .push_bind(&entry.currency)
+                    .push_bind(&entry.description)
+                    .push_bind(type_id)
+                    .push_bind(&entry.external_id),
+                Err(error) => values
+                    .push_bind(error)
+                    .push_bind(None::<DateTime<Utc>>)
+                    .push_bind(None::<TicketDirection>)
+                    .push_bind(None::<Decimal>)
+                    .push_bind(None::<String>)
+                    .push_bind(None::<String>)
+                    .push_bind(None::<Uuid>)
+                    .push_bind(None::<String>),
+            };
+        });
+        builder.build().execute(&mut *transaction).await?;
+    }
+    sqlx::query!(
+        r#"UPDATE tbl_import_row r
+        SET duplicate = TRUE
+        WHERE
+            r.import_id = $1
+            AND (
+                EXISTS (
+                    SELECT 1 FROM accounting_movement_tbl amt
+                    WHERE amt.accounting_id = $2 AND amt.external_id = r.external_id
+                )
+                OR EXISTS (
+                    SELECT 1 FROM tbl_import_row earlier
+                    WHERE
+                        earlier.import_id = r.import_id
+                        AND earlier.external_id = r.external_id
+                        AND earlier.line < r.line
+                )
+            )"#,
+        import_id,
+        accounting_id,
+    )
+    .execute(&mut *transaction)
+    .await?;
+    transaction.commit().await?;
+    Ok(import_id)
+}
+
+pub async fn preview(
+    pool: &PgPool,
+    user_id: Uuid,
+    import_id: Uuid,
+) -> Result<Vec<StagedRow>, TicketError> {
+    let rows = sqlx::query_as!(
+        StagedRow,
+        r#"SELECT
+            r.line,
+            r.error,
+            r.created_at,
+            r.direction as "direction: TicketDirection",
+            ROUND(r.amount, tc.minor_units) as amount,
+            r.currency,
+            r.description,
+            tt.name as "category?",
+            r.duplicate
+        FROM tbl_import_row r
+          INNER JOIN tbl_import ti ON ti.id = r.import_id
+          LEFT JOIN tbl_type tt ON tt.id = r.type_id
+          LEFT JOIN tbl_currency tc ON tc.code = r.currency
+        WHERE
+            ti.id = $1 AND ti.user_id = $2
+        ORDER BY r.line"#,
+        import_id,
+        user_id,
+    )
+    .fetch_all(pool)
+    .await?;
+    if rows.is_empty() {
+        return Err(TicketError::NotFound);
+    }
+    Ok(rows)
+}
+
+/// Write the valid rows that aren't duplicates in one transaction, returns how many were created.
+pub async fn commit(pool: &PgPool, user_id: Uuid, import_id: Uuid) -> Result<u64, TicketError> {
+    let mut transaction = pool.begin().await?;
+    let import = sqlx::query!(
+        r#"SELECT
+            ti.accounting_id,
+            EXISTS (
+                SELECT 1 FROM tbl_accounting_member tam
+                WHERE
+                    tam.accounting_id = ti.accounting_id AND tam.user_id = ti.user_id
+                    AND tam.role <> 'viewer'
+            ) as "writable!"
+        FROM tbl_import ti
+        WHERE ti.id = $1 AND ti.user_id = $2
+        FOR UPDATE"#,
+        import_id,
+        user_id,
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    .ok_or(TicketError::NotFound)?;
+    // the role of the user may have changed since the statement was staged
+    if !import.writable {
+        return Err(TicketError::Forbidden);
+    }
+    let accounting_id = import.accounting_id;
+    // another import may have committed the same rows since the preview
+    let result = sqlx::query!(
+        r#"INSERT INTO accounting_movement_tbl(
+            accounting_id, type_id, direction, amount, currency, description, created_at, external_id,
+            created_by
+        )
+        SELECT $1, type_id, direction, amount, currency, description, created_at, external_id, $3
+        FROM tbl_import_row
+        WHERE
+            import_id = $2 AND error IS NULL AND NOT duplicate
+        ORDER BY line
+        ON CONFLICT (accounting_id, external_id) DO NOTHING"#,
+        accounting_id,
+        import_id,
+        user_id,
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!("DELETE FROM tbl_import WHERE id = $1", import_id)
+        .execute(&mut *transaction)
+        .await?;
+    transaction.commit().await?;
+    Ok(result.rows_affected())
+}
+
+/// Drop a staged import without writing anything.
+pub async fn discard(pool: &PgPool, user_id: Uuid, import_id: Uuid) -> Result<(), TicketError> {
+    let result = sqlx::query!(
+        "DELETE FROM tbl_import WHERE id = $1 AND user_id = $2",
+        import_id,
+        user_id,
+    )
+    .execute(pool)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(TicketError::NotFound);
+    }
+    Ok(())
+}
@@ -0,0 +1,131 @@
+//! OFX and QFX statements, in both their SGML (1.x) and XML (2.x) flavours.
+//!
+//! SGML leaves the elements unclosed (`<TRNAMT>-12.50`), so the file is read as a flat list of
+//! tags with their text instead of going through an XML parser.
+use super::{decode_text, Entry, ParsedRow};
+use crate::model::direction::TicketDirection;
+use chrono::{NaiveDate, NaiveTime};
+use sqlx::types::Decimal;
+use std::{collections::HashMap, str::FromStr};
+
+struct Transaction {
+    line: i32,
+    fields: HashMap<String, String>,
+}
+
+/// Read the transactions of every statement in the file.
+pub fn parse(data: &[u8]) -> Result<Vec<ParsedRow>, &'static str> {
+    let text = decode_text(data);
+    let start = text
+        .to_ascii_uppercase()
+        .find("<OFX>")
+        .ok_or("not an OFX statement")?;
+    let mut line = text[..start].matches('\n').count() as i32 + 1;
+    let mut account: Option<String> = None;
+    let mut currency: Option<String> = None;
+    let mut current: Option<Transaction> = None;
+    let mut rows = Vec::new();
+    for chunk in text[start..].split('<').skip(1) {
+        let tag_line = line;
+        line += chunk.matches('\n').count() as i32;
+        let Some((tag, value)) = chunk.split_once('>') else {
+            continue;
+        };
+        let value = decode_entities(value.trim());
+        match tag.trim().to_ascii_uppercase().as_str() {
+            "STMTTRN" => {
+                if let Some(transaction) = current.take() {
+                    rows.push(transaction.into_row(account.as_deref(), currency.as_deref()));
+                }
+                current = Some(Transaction {
+                    line: tag_line,
+                    fields: HashMap::new(),
+                });
+            }
+            "/STMTTRN" => {
+                if let Some(transaction) = current.take() {
+                    rows.push(transaction.into_row(account.as_deref(), currency.as_deref()));
+                }
+            }
+            // the account of the statement, transfers also have one inside the transaction
+            "ACCTID" if current.is_none() => account = Some(value),
+            "CURDEF" if current.is_none() => currency = Some(value),
+            name if !name.starts_with(['/', '?', '!']) => {
+                if let Some(transaction) = current.as_mut() {
+                    transaction.fields.insert(name.to_string(), value);
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some(transaction) = current.take() {
+        rows.push(transaction.into_row(account.as_deref(), currency.as_deref()));
+    }
+    Ok(rows)
+}
+
+impl Transaction {
+    fn into_row(self, account: Option<&str>, currency: Option<&str>) -> ParsedRow {
+        ParsedRow {
+            line: self.line,
+            entry: self.entry(account, currency),
+        }
+    }
+
+    fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .get(name)
+            .map(String::as_str)
+            .filter(|value| !value.is_empty())
+    }
+
+    fn entry(&self, account: Option<&str>, currency: Option<&str>) -> Result<Entry, String> {
+        let fitid = self.field("FITID").ok_or("missing FITID")?;
+        let date = self.field("DTPOSTED").ok_or("missing DTPOSTED")?;
+        // YYYYMMDD, optionally followed by the time and the timezone
+        let created_at = date
+            .get(..8)
+            .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+            .ok_or_else(|| format!("invalid date '{date}'"))?
+            .and_time(NaiveTime::MIN)
+            .and_utc();
+        let amount = self.field("TRNAMT").ok_or("missing TRNAMT")?;
+        let amount = parse_amount(amount).ok_or_else(|| format!("invalid amount '{amount}'"))?;
+        let description = match (self.field("NAME"), self.field("MEMO")) {
+            (Some(name), Some(memo)) if name != memo => format!("{name} - {memo}"),
+            (Some(text), _) | (None, Some(text)) => text.to_string(),
+            (None, None) => String::new(),
+        };
+        Ok(Entry {
+            created_at,
+            direction: if amount.is_sign_negative() {
+                TicketDirection::Out
+            } else {
+                TicketDirection::In
+            },
+            amount: amount.abs(),
+            description,
+            category: None,
+            currency: currency.map(str::to_string),
+            external_id: match account {
+                Some(account) => format!("ofx:{account}:{fitid}"),
+                None => format!("ofx:{fitid}"),
+            },
+        })
+    }
+}
+
+/// Some banks write the decimal separator as a comma.
+fn parse_amount(amount: &str) -> Option<Decimal> {
+    let amount = amount.replace(',', ".");
+    Decimal::from_str(amount.strip_prefix('+').unwrap_or(&amount)).ok()
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
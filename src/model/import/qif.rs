@@ -0,0 +1,133 @@
+//! Quicken Interchange Format statements.
+//!
+//! QIF has no transaction ids, rows are told apart by their fingerprint like CSV rows. Quicken
+//! categories (`L` lines) rarely match the user's ones, so rows get the default category of the
+//! import.
+use super::{decode_text, Entry, Fingerprints, ParsedRow};
+use crate::model::direction::TicketDirection;
+use chrono::{NaiveDate, NaiveTime};
+use sqlx::types::Decimal;
+use std::str::FromStr;
+
+/// Account types holding plain transactions, investment and list sections are skipped.
+const TRANSACTION_TYPES: [&str; 5] = ["bank", "cash", "ccard", "oth a", "oth l"];
+
+#[derive(Default)]
+struct Record {
+    line: i32,
+    date: Option<String>,
+    amount: Option<String>,
+    payee: Option<String>,
+    memo: Option<String>,
+}
+
+pub fn parse(data: &[u8]) -> Result<Vec<ParsedRow>, &'static str> {
+    let text = decode_text(data);
+    if !text.trim_start().starts_with("!Type:") {
+        return Err("not a QIF statement");
+    }
+    let mut fingerprints = Fingerprints::default();
+    let mut rows = Vec::new();
+    let mut in_transactions = false;
+    let mut record: Option<Record> = None;
+    for (index, text_line) in text.lines().enumerate() {
+        let line = index as i32 + 1;
+        let text_line = text_line.trim_end();
+        if let Some(header) = text_line.strip_prefix('!') {
+            if let Some(account_type) = header.strip_prefix("Type:") {
+                in_transactions =
+                    TRANSACTION_TYPES.contains(&account_type.trim().to_lowercase().as_str());
+            }
+            record = None;
+            continue;
+        }
+        if !in_transactions || text_line.is_empty() {
+            continue;
+        }
+        if text_line.starts_with('^') {
+            if let Some(record) = record.take() {
+                let entry = record.entry().map(|mut entry| {
+                    entry.external_id = fingerprints.external_id("qif", &entry);
+                    entry
+                });
+                rows.push(ParsedRow {
+                    line: record.line,
+                    entry,
+                });
+            }
+            continue;
+        }
+        let current = record.get_or_insert_with(|| Record {
+            line,
+            ..Default::default()
+        });
+        let (code, value) = text_line.split_at(1);
+        let value = Some(value.trim().to_string());
+        match code {
+            "D" => current.date = value,
+            "T" | "U" => current.amount = value,
+            "P" => current.payee = value,
+            "M" => current.memo = value,
+            _ => {}
+        }
+    }
+    Ok(rows)
+}
+
+impl Record {
+    fn entry(&self) -> Result<Entry, String> {
+        let date = self.date.as_deref().ok_or("missing date")?;
+        let created_at = parse_date(date)
+            .ok_or_else(|| format!("invalid date '{date}'"))?
+            .and_time(NaiveTime::MIN)
+            .and_utc();
+        let amount = self.amount.as_deref().ok_or("missing amount")?;
+        let parsed = parse_amount(amount).ok_or_else(|| format!("invalid amount '{amount}'"))?;
+        let description = match (&self.payee, &self.memo) {
+            (Some(payee), Some(memo)) if payee != memo => format!("{payee} - {memo}"),
+            (Some(text), _) | (None, Some(text)) => text.clone(),
+            (None, None) => String::new(),
+        };
+        Ok(Entry {
+            created_at,
+            direction: if parsed.is_sign_negative() {
+                TicketDirection::Out
+            } else {
+                TicketDirection::In
+            },
+            amount: parsed.abs(),
+            description,
+            category: None,
+            currency: None,
+            external_id: String::new(),
+        })
+    }
+}
+
+/// Amounts are written `1,234.56`, or `1.234,56` by European versions: a comma followed by
+/// exactly two digits at the end is the decimal separator.
+fn parse_amount(amount: &str) -> Option<Decimal> {
+    let decimal_comma = amount
+        .rsplit_once(',')
+        .is_some_and(|(_, cents)| cents.len() == 2 && cents.bytes().all(|c| c.is_ascii_digit()));
+    let amount: String = if decimal_comma {
+        amount.replace('.', "").replace(',', ".")
+    } else {
+        amount.replace(',', "")
+    };
+    Decimal::from_str(&amount).ok()
+}
+
+/// Quicken writes US dates, padded with spaces and with an apostrophe before 2-digit years
+/// after 1999 (`1/ 5'24`).
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    let date: String = date
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| if c == '\'' { '/' } else { c })
+        .collect();
+    // 2-digit years first, `%Y` would read them as years of the first century
+    ["%m/%d/%y", "%m/%d/%Y", "%Y-%m-%d", "%d.%m.%Y"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(&date, format).ok())
+}
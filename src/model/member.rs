@@ -0,0 +1,389 @@
+//! Membership of the users in accounting books. Every member can read the tickets and reports
+//! of a book, editors and owners can write them, and only owners manage the book and who's in
+//! it. Users join a book by accepting an invitation from one of its owners.
+
+use crate::model::ticket::TicketError;
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Clone, Copy, Debug, PartialEq, sqlx::Type, serde::Deserialize, serde::Serialize)]
+#[sqlx(type_name = "BOOK_ROLE", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum BookRole {
+    Owner,
+    Editor,
+    Viewer,
+}
+
+impl BookRole {
+    pub fn can_write(self) -> bool {
+        self != Self::Viewer
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum MemberError {
+    #[error("{0}")]
+    Invalid(&'static str),
+    /// The book, member or invitation doesn't exist, or the user can't see it.
+    #[error("not found")]
+    NotFound,
+    /// The user is a member of the book, but their role doesn't allow the operation.
+    #[error("not allowed in this book")]
+    Forbidden,
+    #[error(transparent)]
+    UnexpectedError(#[from] sqlx::Error),
+}
+
+impl IntoResponse for MemberError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Invalid(message) => (StatusCode::BAD_REQUEST, message).into_response(),
+            Self::NotFound => StatusCode::NOT_FOUND.into_response(),
+            Self::Forbidden => (StatusCode::FORBIDDEN, self.to_string()).into_response(),
+            Self::UnexpectedError(e) => {
+                tracing::error!("Failed member operation: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        }
+    }
+}
+
+/// The role checks guard the writes of tickets too, where a book the user can't see is a
+/// ticket not found.
+impl From<MemberError> for TicketError {
+    fn from(e: MemberError) -> Self {
+        match e {
+            MemberError::Invalid(message) => Self::Invalid(message),
+            MemberError::NotFound => Self::NotFound,
+            MemberError::Forbidden => Self::Forbidden,
+            MemberError::UnexpectedError(e) => Self::UnexpectedError(e),
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct Member {
+    pub user_id: Uuid,
+    pub username: String,
+    pub role: BookRole,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct Invitation {
+    pub id: Uuid,
+    pub accounting_id: Uuid,
+    pub accounting: String,
+    pub role: BookRole,
+    /// Username of the owner who sent the invitation.
+    pub invited_by: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// The role of the user in a book, or in their current book when `accounting_id` is `None`.
+/// `None` when they aren't a member.
+pub async fn book_role(
+    pool: &PgPool,
+    user_id: Uuid,
+    accounting_id: Option<Uuid>,
+) -> Result<Option<BookRole>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"SELECT role as "role: BookRole"
+        FROM tbl_accounting_member
+        WHERE
+            accounting_id = COALESCE($1, (SELECT current_accounting_id FROM tbl_user WHERE id = $2))
+            AND user_id = $2"#,
+        accounting_id,
+        user_id,
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|row| row.role))
+}
+
+/// The role of the user in the book of a ticket, `None` when they can't see the ticket.
+pub async fn ticket_role(
+    pool: &PgPool,
+    user_id: Uuid,
+    ticket_id: Uuid,
+) -> Result<Option<BookRole>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"SELECT tam.role as "role: BookRole"
+        FROM accounting_movement_tbl amt
+          INNER JOIN tbl_accounting_member tam ON tam.accounting_id = amt.accounting_id
+        WHERE amt.id = $1 AND tam.user_id = $2"#,
+        ticket_id,
+        user_id,
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|row| row.role))
+}
+
+/// Check a role allows writing tickets, something the user can't see is reported as missing.
+pub fn require_write(role: Option<BookRole>) -> Result<(), MemberError> {
+    match role {
+        None => Err(MemberError::NotFound),
+        Some(role) if !role.can_write() => Err(MemberError::Forbidden),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Reject viewers, a book the user isn't a member of is left to the write itself to report.
+pub fn forbid_viewer(role: Option<BookRole>) -> Result<(), MemberError> {
+    match role {
+        Some(role) if !role.can_write() => Err(MemberError::Forbidden),
+        _ => Ok(()),
+    }
+}
+
+/// Check the user owns a book, only owners manage the book and its members.
+pub async fn require_owner(
+    pool: &PgPool,
+    user_id: Uuid,
+    accounting_id: Uuid,
+) -> Result<(), MemberError> {
+    match book_role(pool, user_id, Some(accounting_id)).await? {
+        None => Err(MemberError::NotFound),
+        Some(BookRole::Owner) => Ok(()),
+        Some(_) => Err(MemberError::Forbidden),
+    }
+}
+
+/// The members of a book, visible to all of them.
+pub async fn members(
+    pool: &PgPool,
+    user_id: Uuid,
+    accounting_id: Uuid,
+) -> Result<Vec<Member>, MemberError> {
+    if book_role(pool, user_id, Some(accounting_id))
+        .await?
+        .is_none()
+    {
+        return Err(MemberError::NotFound);
+    }
+    let members = sqlx::query_as!(
+        Member,
+        r#"SELECT tam.user_id, tu.username, tam.role as "role: BookRole", tam.created_at
+        FROM tbl_accounting_member tam
+          INNER JOIN tbl_user tu ON tu.id = tam.user_id
+        WHERE tam.accounting_id = $1
+        ORDER BY tam.created_at"#,
+        accounting_id,
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(members)
+}
+
+/// Invite the user with this username or email to a book, inviting them again changes the role
+/// they're offered.
+pub async fn invite(
+    pool: &PgPool,
+    user_id: Uuid,
+    accounting_id: Uuid,
+    username_or_email: &str,
+    role: BookRole,
+) -> Result<Uuid, MemberError> {
+    require_owner(pool, user_id, accounting_id).await?;
+    let invited = sqlx::query!(
+        r#"SELECT
+            tu.id,
+            EXISTS (
+                SELECT 1 FROM tbl_accounting_member
+                WHERE accounting_id = $2 AND user_id = tu.id
+            ) as "member!"
+        FROM tbl_user tu
+        WHERE tu.username = $1 OR lower(tu.email) = lower($1)"#,
+        username_or_email.trim(),
+        accounting_id,
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(MemberError::Invalid("no user with this username or email"))?;
+    if invited.member {
+        return Err(MemberError::Invalid("already a member of the book"));
+    }
+    let row = sqlx::query!(
+        r#"INSERT INTO tbl_invitation(accounting_id, user_id, role, invited_by)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (accounting_id, user_id)
+        DO UPDATE SET role = EXCLUDED.role, invited_by = EXCLUDED.invited_by
+        RETURNING id"#,
+        accounting_id,
+        invited.id,
+        role as BookRole,
+        user_id,
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(row.id)
+}
+
+/// The invitations waiting for the user.
+pub async fn invitations(pool: &PgPool, user_id: Uuid) -> Result<Vec<Invitation>, sqlx::Error> {
+    sqlx::query_as!(
+        Invitation,
+        r#"SELECT
+            ti.id,
+            ti.accounting_id,
+            ta.name as accounting,
+            ti.role as "role: BookRole",
+            tu.username as invited_by,
+            ti.created_at
+        FROM tbl_invitation ti
+          INNER JOIN tbl_accounting ta ON ta.id = ti.accounting_id
+          INNER JOIN tbl_user tu ON tu.id = ti.invited_by
+        WHERE ti.user_id = $1
+        ORDER BY ti.created_at"#,
+        user_id,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Join the book of an invitation with the role it offers.
+pub async fn accept(pool: &PgPool, user_id: Uuid, invitation_id: Uuid) -> Result<(), MemberError> {
+    let mut transaction = pool.begin().await?;
+    let invitation = sqlx::query!(
+        r#"DELETE FROM tbl_invitation
+        WHERE id = $1 AND user_id = $2
+        RETURNING accounting_id, role as "role: BookRole""#,
+        invitation_id,
+        user_id,
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    .ok_or(MemberError::NotFound)?;
+    sqlx::query!(
+        r#"INSERT INTO tbl_accounting_member(accounting_id, user_id, role)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (accounting_id, user_id) DO NOTHING"#,
+        invitation.accounting_id,
+        user_id,
+        invitation.role as BookRole,
+    )
+    .execute(&mut *transaction)
+    .await?;
+    transaction.commit().await?;
+    Ok(())
+}
+
+/// Decline an invitation as the invited user, or withdraw it as an owner of the book.
+pub async fn delete_invitation(
+    pool: &PgPool,
+    user_id: Uuid,
+    invitation_id: Uuid,
+) -> Result<(), MemberError> {
+    let result = sqlx::query!(
+        r#"DELETE FROM tbl_invitation ti
+        WHERE
+            ti.id = $1
+            AND (ti.user_id = $2 OR EXISTS (
+                SELECT 1 FROM tbl_accounting_member tam
+                WHERE tam.accounting_id = ti.accounting_id AND tam.user_id = $2 AND tam.role = 'owner'
+            ))"#,
+        invitation_id,
+        user_id,
+    )
+    .execute(pool)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(MemberError::NotFound);
+    }
+    Ok(())
+}
+
+/// Lock the members of a book and check the change leaves it with an owner.
+async fn check_owner_left(
+    connection: &mut sqlx::PgConnection,
+    accounting_id: Uuid,
+    member_id: Uuid,
+) -> Result<(), MemberError> {
+    let owners = sqlx::query_scalar!(
+        r#"SELECT user_id
+        FROM tbl_accounting_member
+        WHERE accounting_id = $1 AND role = 'owner'
+        FOR UPDATE"#,
+        accounting_id,
+    )
+    .fetch_all(connection)
+    .await?;
+    if owners == [member_id] {
+        return Err(MemberError::Invalid("a book needs at least one owner"));
+    }
+    Ok(())
+}
+
+/// Change the role of a member, owners can't leave the book without an owner.
+pub async fn set_role(
+    pool: &PgPool,
+    user_id: Uuid,
+    accounting_id: Uuid,
+    member_id: Uuid,
+    role: BookRole,
+) -> Result<(), MemberError> {
+    require_owner(pool, user_id, accounting_id).await?;
+    let mut transaction = pool.begin().await?;
+    if role != BookRole::Owner {
+        check_owner_left(&mut transaction, accounting_id, member_id).await?;
+    }
+    let result = sqlx::query!(
+        "UPDATE tbl_accounting_member SET role = $3 WHERE accounting_id = $1 AND user_id = $2",
+        accounting_id,
+        member_id,
+        role as BookRole,
+    )
+    .execute(&mut *transaction)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(MemberError::NotFound);
+    }
+    transaction.commit().await?;
+    Ok(())
+}
+
+/// Remove a member as an owner, or leave the book as any member. The tickets they created stay.
+pub async fn remove(
+    pool: &PgPool,
+    user_id: Uuid,
+    accounting_id: Uuid,
+    member_id: Uuid,
+) -> Result<(), MemberError> {
+    if member_id != user_id {
+        require_owner(pool, user_id, accounting_id).await?;
+    } else if book_role(pool, user_id, Some(accounting_id))
+        .await?
+        .is_none()
+    {
+        return Err(MemberError::NotFound);
+    }
+    let mut transaction = pool.begin().await?;
+    check_owner_left(&mut transaction, accounting_id, member_id).await?;
+    let result = sqlx::query!(
+        "DELETE FROM tbl_accounting_member WHERE accounting_id = $1 AND user_id = $2",
+        accounting_id,
+        member_id,
+    )
+    .execute(&mut *transaction)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(MemberError::NotFound);
+    }
+    sqlx::query!(
+        r#"UPDATE tbl_user
+        SET current_accounting_id = NULL
+        WHERE id = $1 AND current_accounting_id = $2"#,
+        member_id,
+        accounting_id,
+    )
+    .execute(&mut *transaction)
+    .await?;
+    transaction.commit().await?;
+    Ok(())
+}
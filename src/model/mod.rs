@@ -1 +1,12 @@
+pub mod attachment;
+pub mod budget;
+pub mod category;
+pub mod currency;
 pub mod direction;
+pub mod import;
+pub mod member;
+pub mod recurring;
+pub mod report;
+pub mod tag;
+pub mod ticket;
+pub mod transfer;
@@ -0,0 +1,403 @@
+mod schedule;
+
+pub use schedule::{Frequency, Schedule};
+
+use crate::model::{
+    currency::normalize_code,
+    direction::TicketDirection,
+    member::{book_role, forbid_viewer, require_write, BookRole},
+    ticket::{
+        validation::{is_usable_type, user_error, validate_amount},
+        TicketError,
+    },
+};
+use chrono::{NaiveDate, NaiveTime};
+use sqlx::{types::Decimal, PgPool};
+use uuid::Uuid;
+
+/// A rule creating the same ticket on a schedule.
+#[derive(Debug, serde::Serialize)]
+pub struct Recurring {
+    pub id: Uuid,
+    pub accounting_id: Uuid,
+    pub type_id: Uuid,
+    pub direction: TicketDirection,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
+    pub currency: String,
+    pub description: String,
+    pub frequency: Frequency,
+    pub day_of_month: Option<i16>,
+    pub starts_on: NaiveDate,
+    pub ends_on: Option<NaiveDate>,
+    pub max_occurrences: Option<i32>,
+    pub occurrences: i32,
+    pub last_occurrence: Option<NaiveDate>,
+    pub next_due: Option<NaiveDate>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct RecurringInput {
+    pub direction: TicketDirection,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
+    pub type_id: Uuid,
+    pub description: String,
+    /// Defaults to the user's current book on creation, left untouched on update.
+    pub accounting_id: Option<Uuid>,
+    /// Defaults to the currency of the book on creation, left untouched on update.
+    pub currency: Option<String>,
+    pub frequency: Frequency,
+    pub day_of_month: Option<i16>,
+    pub starts_on: NaiveDate,
+    pub ends_on: Option<NaiveDate>,
+    pub max_occurrences: Option<i32>,
+}
+
+impl RecurringInput {
+    fn schedule(&self) -> Schedule {
+        Schedule {
+            frequency: self.frequency,
+            starts_on: self.starts_on,
+            day_of_month: self.day_of_month,
+            ends_on: self.ends_on,
+            max_occurrences: self.max_occurrences,
+        }
+    }
+}
+
+/// The role of the user in the book of a rule, `None` when they can't see the rule.
+async fn rule_role(
+    pool: &PgPool,
+    user_id: Uuid,
+    recurring_id: Uuid,
+) -> Result<Option<BookRole>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"SELECT tam.role as "role: BookRole"
+        FROM tbl_recurring tr
+          INNER JOIN tbl_accounting_member tam ON tam.accounting_id = tr.accounting_id
+        WHERE tr.id = $1 AND tam.user_id = $2"#,
+        recurring_id,
+        user_id,
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|row| row.role))
+}
+
+async fn validate(
+    pool: &PgPool,
+    user_id: Uuid,
+    input: &RecurringInput,
+    recurring_id: Option<Uuid>,
+) -> Result<(), TicketError> {
+    if let Some(recurring_id) = recurring_id {
+        require_write(rule_role(pool, user_id, recurring_id).await?)?;
+    }
+    if recurring_id.is_none() || input.accounting_id.is_some() {
+        forbid_viewer(book_role(pool, user_id, input.accounting_id).await?)?;
+    }
+    let currency = input.currency.as_deref().map(normalize_code);
+    // same rule as tickets: the given currency, else the rule's own, else the book's
+    let minor_units = sqlx::query!(
+        r#"SELECT tc.minor_units
+        FROM tbl_currency tc
+        WHERE tc.code = COALESCE(
+            $1,
+            (
+                SELECT tr.currency
+                FROM tbl_recurring tr
+                  INNER JOIN tbl_accounting_member tam ON tam.accounting_id = tr.accounting_id
+                WHERE tr.id = $2 AND tam.user_id = $3
+            ),
+            (
+                SELECT ta.currency
+                FROM tbl_accounting ta
+                  INNER JOIN tbl_accounting_member tam ON tam.accounting_id = ta.id
+                WHERE
+                    tam.user_id = $3
+                    AND ta.id = COALESCE($4, (SELECT current_accounting_id FROM tbl_user WHERE id = $3))
+            )
+        )"#,
+        currency,
+        recurring_id,
+        user_id,
+        input.accounting_id,
+    )
+    .fetch_optional(pool)
+    .await?
+    .map(|row| row.minor_units as u32);
+    let minor_units = match minor_units {
+        Some(minor_units) => minor_units,
+        None if currency.is_some() => return Err(TicketError::Invalid("unknown currency")),
+        None => 2,
+    };
+    validate_amount(&input.amount, minor_units).map_err(TicketError::Invalid)?;
+    input.schedule().validate().map_err(TicketError::Invalid)?;
+    if !is_usable_type(pool, user_id, input.type_id, None).await? {
+        return Err(TicketError::Invalid("unknown type_id"));
+    }
+    Ok(())
+}
+
+fn map_write_error(error: sqlx::Error) -> TicketError {
+    match user_error(&error) {
+        Some(message) => TicketError::Invalid(message),
+        None => error.into(),
+    }
+}
+
+pub async fn list(pool: &PgPool, user_id: Uuid) -> Result<Vec<Recurring>, TicketError> {
+    let rules = sqlx::query_as!(
+        Recurring,
+        r#"SELECT
+            tr.id,
+            tr.accounting_id,
+            tr.type_id,
+            tr.direction as "direction: TicketDirection",
+            tr.amount,
+            tr.currency,
+            tr.description,
+            tr.frequency as "frequency: Frequency",
+            tr.day_of_month,
+            tr.starts_on,
+            tr.ends_on,
+            tr.max_occurrences,
+            tr.occurrences,
+            tr.last_occurrence,
+            tr.next_due
+        FROM tbl_recurring tr
+          INNER JOIN tbl_accounting_member tam ON tam.accounting_id = tr.accounting_id
+        WHERE
+            tam.user_id = $1
+        ORDER BY tr.next_due NULLS LAST, tr.created_at"#,
+        user_id,
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rules)
+}
+
+pub async fn create(
+    pool: &PgPool,
+    user_id: Uuid,
+    input: &RecurringInput,
+) -> Result<Uuid, TicketError> {
+    validate(pool, user_id, input, None).await?;
+    let next_due = input.schedule().next_due(None, 0);
+    let row = sqlx::query!(
+        r#"INSERT INTO tbl_recurring(
+            accounting_id, type_id, direction, amount, description,
+            frequency, day_of_month, starts_on, ends_on, max_occurrences, next_due, currency,
+            created_by
+        )
+        SELECT ta.id,$1,$2,ROUND($3, tc.minor_units),$4,$5,$6,$7,$8,$9,$10,tc.code,$11
+        FROM tbl_accounting ta
+          INNER JOIN tbl_currency tc ON tc.code = COALESCE($13, ta.currency)
+        WHERE
+          EXISTS (
+            SELECT 1 FROM tbl_accounting_member tam
+            WHERE tam.accounting_id = ta.id AND tam.user_id = $11 AND tam.role <> 'viewer'
+          )
+          AND ta.id = COALESCE($12, (SELECT current_accounting_id FROM tbl_user WHERE id = $11))
+        RETURNING id"#,
+        input.type_id,
+        input.direction.clone() as TicketDirection,
+        input.amount,
+        input.description,
+        input.frequency as Frequency,
+        input.day_of_month,
+        input.starts_on,
+        input.ends_on,
+        input.max_occurrences,
+        next_due,
+        user_id,
+        input.accounting_id,
+        input.currency.as_deref().map(normalize_code),
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(map_write_error)?;
+    match row {
+        Some(row) => Ok(row.id),
+        None if input.accounting_id.is_some() => Err(TicketError::Invalid("unknown accounting_id")),
+        None => Err(TicketError::Invalid("no accounting selected")),
+    }
+}
+
+/// Update a rule, the tickets it already created are left untouched.
+pub async fn update(
+    pool: &PgPool,
+    user_id: Uuid,
+    recurring_id: Uuid,
+    input: &RecurringInput,
+) -> Result<(), TicketError> {
+    validate(pool, user_id, input, Some(recurring_id)).await?;
+    let mut transaction = pool.begin().await?;
+    // lock the rule so the scheduler can't advance it in the meantime
+    let current = sqlx::query!(
+        r#"SELECT tr.occurrences, tr.last_occurrence
+        FROM tbl_recurring tr
+          INNER JOIN tbl_accounting_member tam ON tam.accounting_id = tr.accounting_id
+        WHERE
+            tr.id = $1 AND tam.user_id = $2 AND tam.role <> 'viewer'
+        FOR UPDATE OF tr"#,
+        recurring_id,
+        user_id,
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    .ok_or(TicketError::NotFound)?;
+    let next_due = input
+        .schedule()
+        .next_due(current.last_occurrence, current.occurrences);
+    let result = sqlx::query!(
+        r#"UPDATE tbl_recurring tr
+        SET
+            type_id = $1,
+            direction = $2,
+            amount = ROUND($3, tc.minor_units),
+            currency = tc.code,
+            description = $4,
+            frequency = $5,
+            day_of_month = $6,
+            starts_on = $7,
+            ends_on = $8,
+            max_occurrences = $9,
+            next_due = $10,
+            accounting_id = COALESCE($11, tr.accounting_id)
+        FROM tbl_currency tc
+        WHERE
+            tr.id = $12
+            AND tc.code = COALESCE($14, tr.currency)
+            AND ($11::uuid IS NULL OR EXISTS (
+                SELECT 1 FROM tbl_accounting_member
+                WHERE accounting_id = $11 AND user_id = $13 AND role <> 'viewer'
+            ))"#,
+        input.type_id,
+        input.direction.clone() as TicketDirection,
+        input.amount,
+        input.description,
+        input.frequency as Frequency,
+        input.day_of_month,
+        input.starts_on,
+        input.ends_on,
+        input.max_occurrences,
+        next_due,
+        input.accounting_id,
+        recurring_id,
+        user_id,
+        input.currency.as_deref().map(normalize_code),
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(map_write_error)?;
+    if result.rows_affected() == 0 {
+        return Err(TicketError::Invalid("unknown accounting_id"));
+    }
+    transaction.commit().await?;
+    Ok(())
+}
+
+/// Delete a rule, the tickets it already created are kept.
+pub async fn delete(pool: &PgPool, user_id: Uuid, recurring_id: Uuid) -> Result<(), TicketError> {
+    require_write(rule_role(pool, user_id, recurring_id).await?)?;
+    let result = sqlx::query!(
+        r#"DELETE FROM tbl_recurring tr
+        USING tbl_accounting_member tam
+        WHERE
+            tam.accounting_id = tr.accounting_id AND tr.id = $1
+            AND tam.user_id = $2 AND tam.role <> 'viewer'"#,
+        recurring_id,
+        user_id,
+    )
+    .execute(pool)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(TicketError::NotFound);
+    }
+    Ok(())
+}
+
+/// Create the tickets of every occurrence due on `today` or earlier, returns how many were created.
+///
+/// Due rules are locked with `SKIP LOCKED`, so several instances can run this at the same time,
+/// and the unique `(recurring_id, created_at)` index keeps an occurrence from being created twice.
+pub async fn materialize_due(pool: &PgPool, today: NaiveDate) -> Result<u64, sqlx::Error> {
+    let mut transaction = pool.begin().await?;
+    let rules = sqlx::query!(
+        r#"SELECT
+            id,
+            accounting_id,
+            type_id,
+            direction as "direction: TicketDirection",
+            amount,
+            currency,
+            description,
+            frequency as "frequency: Frequency",
+            day_of_month,
+            starts_on,
+            ends_on,
+            max_occurrences,
+            occurrences,
+            last_occurrence,
+            next_due as "next_due!",
+            created_by
+        FROM tbl_recurring
+        WHERE
+            next_due <= $1
+        FOR UPDATE SKIP LOCKED"#,
+        today,
+    )
+    .fetch_all(&mut *transaction)
+    .await?;
+    let mut created = 0;
+    for rule in rules {
+        let schedule = Schedule {
+            frequency: rule.frequency,
+            starts_on: rule.starts_on,
+            day_of_month: rule.day_of_month,
+            ends_on: rule.ends_on,
+            max_occurrences: rule.max_occurrences,
+        };
+        let mut occurrences = rule.occurrences;
+        let mut last_occurrence = rule.last_occurrence;
+        let mut next_due = Some(rule.next_due);
+        while let Some(due) = next_due.filter(|due| *due <= today) {
+            let result = sqlx::query!(
+                r#"INSERT INTO accounting_movement_tbl(
+                    accounting_id, type_id, direction, amount, description, created_at, recurring_id,
+                    currency, created_by
+                )
+                VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9)
+                ON CONFLICT (recurring_id, created_at) DO NOTHING"#,
+                rule.accounting_id,
+                rule.type_id,
+                rule.direction.clone() as TicketDirection,
+                rule.amount,
+                rule.description,
+                due.and_time(NaiveTime::MIN).and_utc(),
+                rule.id,
+                rule.currency,
+                rule.created_by,
+            )
+            .execute(&mut *transaction)
+            .await?;
+            created += result.rows_affected();
+            occurrences += 1;
+            last_occurrence = Some(due);
+            next_due = schedule.next_due(last_occurrence, occurrences);
+        }
+        sqlx::query!(
+            "UPDATE tbl_recurring SET occurrences = $1, last_occurrence = $2, next_due = $3 WHERE id = $4",
+            occurrences,
+            last_occurrence,
+            next_due,
+            rule.id,
+        )
+        .execute(&mut *transaction)
+        .await?;
+    }
+    transaction.commit().await?;
+    Ok(created)
+}
@@ -0,0 +1,219 @@
+use chrono::{Datelike, Days, Months, NaiveDate};
+
+#[derive(Clone, Copy, Debug, PartialEq, sqlx::Type, serde::Deserialize, serde::Serialize)]
+#[sqlx(type_name = "RECURRENCE_FREQUENCY", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// When a recurring ticket happens.
+///
+/// Weekly rules repeat on the weekday of `starts_on` and yearly rules on its day of the year,
+/// monthly rules use `day_of_month` (or the day of `starts_on`) clamped to the end of the month.
+#[derive(Debug)]
+pub struct Schedule {
+    pub frequency: Frequency,
+    pub starts_on: NaiveDate,
+    pub day_of_month: Option<i16>,
+    pub ends_on: Option<NaiveDate>,
+    pub max_occurrences: Option<i32>,
+}
+
+impl Schedule {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        match self.day_of_month {
+            Some(_) if self.frequency != Frequency::Monthly => {
+                return Err("day_of_month is only used by monthly rules")
+            }
+            Some(day) if !(1..=31).contains(&day) => {
+                return Err("day_of_month must be between 1 and 31")
+            }
+            _ => {}
+        }
+        if self.ends_on.is_some_and(|ends_on| ends_on < self.starts_on) {
+            return Err("ends_on must not be before starts_on");
+        }
+        if self.max_occurrences.is_some_and(|max| max < 1) {
+            return Err("max_occurrences must be greater than zero");
+        }
+        Ok(())
+    }
+
+    /// The occurrence following `last`, or the first one when nothing was created yet.
+    ///
+    /// `None` once the end date or the number of occurrences is reached.
+    pub fn next_due(&self, last: Option<NaiveDate>, occurrences: i32) -> Option<NaiveDate> {
+        if self.max_occurrences.is_some_and(|max| occurrences >= max) {
+            return None;
+        }
+        let from = match last {
+            Some(last) => last.succ_opt()?,
+            None => self.starts_on,
+        };
+        self.first_on_or_after(from)
+            .filter(|next| self.ends_on.map_or(true, |ends_on| *next <= ends_on))
+    }
+
+    fn first_on_or_after(&self, date: NaiveDate) -> Option<NaiveDate> {
+        let date = date.max(self.starts_on);
+        match self.frequency {
+            Frequency::Daily => Some(date),
+            Frequency::Weekly => {
+                let weeks = ((date - self.starts_on).num_days() + 6) / 7;
+                self.starts_on.checked_add_days(Days::new(weeks as u64 * 7))
+            }
+            Frequency::Monthly => {
+                let day = self
+                    .day_of_month
+                    .map_or(self.starts_on.day(), |day| day as u32);
+                let candidate = clamped_date(date.year(), date.month(), day)?;
+                if candidate >= date {
+                    return Some(candidate);
+                }
+                let next_month = date.with_day(1)?.checked_add_months(Months::new(1))?;
+                clamped_date(next_month.year(), next_month.month(), day)
+            }
+            Frequency::Yearly => {
+                let (month, day) = (self.starts_on.month(), self.starts_on.day());
+                let candidate = clamped_date(date.year(), month, day)?;
+                if candidate >= date {
+                    return Some(candidate);
+                }
+                clamped_date(date.year() + 1, month, day)
+            }
+        }
+    }
+}
+
+/// The given day of the month, or the last day of the month when it's shorter.
+fn clamped_date(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+    (28..=day)
+        .rev()
+        .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+        .or_else(|| NaiveDate::from_ymd_opt(year, month, day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn schedule(frequency: Frequency, starts_on: NaiveDate) -> Schedule {
+        Schedule {
+            frequency,
+            starts_on,
+            day_of_month: None,
+            ends_on: None,
+            max_occurrences: None,
+        }
+    }
+
+    /// Every occurrence as the recurring job creates them, at most `limit`.
+    fn series(schedule: &Schedule, limit: usize) -> Vec<NaiveDate> {
+        let mut dates: Vec<NaiveDate> = Vec::new();
+        while dates.len() < limit {
+            match schedule.next_due(dates.last().copied(), dates.len() as i32) {
+                Some(next) => dates.push(next),
+                None => break,
+            }
+        }
+        dates
+    }
+
+    #[test]
+    fn monthly_on_the_31st_clamps_to_the_end_of_shorter_months() {
+        let rule = schedule(Frequency::Monthly, date(2027, 1, 31));
+        assert_eq!(
+            series(&rule, 4),
+            [
+                date(2027, 1, 31),
+                date(2027, 2, 28),
+                date(2027, 3, 31),
+                date(2027, 4, 30)
+            ]
+        );
+        let leap = Schedule {
+            day_of_month: Some(31),
+            ..schedule(Frequency::Monthly, date(2028, 1, 5))
+        };
+        assert_eq!(
+            series(&leap, 3),
+            [date(2028, 1, 31), date(2028, 2, 29), date(2028, 3, 31)]
+        );
+        assert_eq!(clamped_date(2027, 2, 31), Some(date(2027, 2, 28)));
+        assert_eq!(clamped_date(2028, 2, 30), Some(date(2028, 2, 29)));
+        assert_eq!(clamped_date(2027, 6, 15), Some(date(2027, 6, 15)));
+        assert_eq!(clamped_date(2027, 13, 1), None);
+    }
+
+    #[test]
+    fn weekly_stays_on_the_weekday_of_starts_on() {
+        // a Wednesday
+        let rule = schedule(Frequency::Weekly, date(2027, 3, 3));
+        assert_eq!(
+            series(&rule, 3),
+            [date(2027, 3, 3), date(2027, 3, 10), date(2027, 3, 17)]
+        );
+        // catching up after a gap lands on the next Wednesday
+        assert_eq!(
+            rule.next_due(Some(date(2027, 3, 12)), 2),
+            Some(date(2027, 3, 17))
+        );
+        assert_eq!(
+            rule.next_due(Some(date(2027, 3, 16)), 2),
+            Some(date(2027, 3, 17))
+        );
+        assert_eq!(
+            rule.next_due(Some(date(2027, 3, 17)), 3),
+            Some(date(2027, 3, 24))
+        );
+    }
+
+    #[test]
+    fn ends_on_is_inclusive() {
+        let rule = Schedule {
+            ends_on: Some(date(2027, 3, 17)),
+            ..schedule(Frequency::Weekly, date(2027, 3, 3))
+        };
+        assert_eq!(
+            series(&rule, 10),
+            [date(2027, 3, 3), date(2027, 3, 10), date(2027, 3, 17)]
+        );
+        let rule = Schedule {
+            ends_on: Some(date(2027, 3, 16)),
+            ..rule
+        };
+        assert_eq!(series(&rule, 10), [date(2027, 3, 3), date(2027, 3, 10)]);
+        let same_day = Schedule {
+            ends_on: Some(date(2027, 3, 3)),
+            ..schedule(Frequency::Daily, date(2027, 3, 3))
+        };
+        assert_eq!(series(&same_day, 10), [date(2027, 3, 3)]);
+    }
+
+    #[test]
+    fn max_occurrences_stops_the_series() {
+        let rule = Schedule {
+            max_occurrences: Some(3),
+            ..schedule(Frequency::Daily, date(2027, 3, 3))
+        };
+        assert_eq!(
+            series(&rule, 10),
+            [date(2027, 3, 3), date(2027, 3, 4), date(2027, 3, 5)]
+        );
+        assert_eq!(rule.next_due(Some(date(2027, 3, 5)), 3), None);
+        // whichever of the end date and the count comes first
+        let rule = Schedule {
+            ends_on: Some(date(2027, 3, 4)),
+            ..rule
+        };
+        assert_eq!(series(&rule, 10), [date(2027, 3, 3), date(2027, 3, 4)]);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::model::ticket::filter::TicketFilter;
+use sqlx::{types::Decimal, PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+/// Length of the buckets the movements are summed in, buckets start at UTC midnight.
+#[derive(Debug, Default, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    Day,
+    Week,
+    #[default]
+    Month,
+    Year,
+}
+
+impl Period {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+            Self::Year => "year",
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, sqlx::FromRow)]
+pub struct PeriodTotal {
+    pub period: chrono::DateTime<chrono::Utc>,
+    /// Base currency of the books the totals are converted to.
+    pub currency: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub total_in: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub total_out: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub net: Decimal,
+    /// Movements left out of the totals because no exchange rate was known at their date.
+    pub unconverted: i64,
+}
+
+#[derive(Debug, serde::Serialize, sqlx::FromRow)]
+pub struct CategoryTotal {
+    pub period: chrono::DateTime<chrono::Utc>,
+    pub currency: String,
+    pub type_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub name: String,
+    /// Totals of the category and of all its children.
+    #[serde(with = "rust_decimal::serde::float")]
+    pub total_in: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub total_out: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub net: Decimal,
+    /// Movements left out of the totals because no exchange rate was known at their date.
+    pub unconverted: i64,
+}
+
+const TOTALS: &str = r#"
+        COALESCE(SUM(m.amount) FILTER (WHERE m.direction = 'in'), 0) as total_in,
+        COALESCE(SUM(m.amount) FILTER (WHERE m.direction = 'out'), 0) as total_out,
+        COALESCE(SUM(CASE WHEN m.direction = 'in' THEN m.amount ELSE -m.amount END), 0) as net,
+        COUNT(*) FILTER (WHERE m.amount IS NULL) as unconverted"#;
+
+#[derive(Debug, serde::Serialize, sqlx::FromRow)]
+pub struct TagTotal {
+    pub period: chrono::DateTime<chrono::Utc>,
+    pub currency: String,
+    pub tag_id: Uuid,
+    pub name: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub total_in: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub total_out: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub net: Decimal,
+    /// Movements left out of the totals because no exchange rate was known at their date.
+    pub unconverted: i64,
+}
+
+/// Push a `movements` CTE with the filtered movements of the user and their period, a split
+/// ticket counts as its lines.
+///
+/// Amounts are converted to the currency of their book with the latest rate known on the day
+/// of the movement, taken from the rates of the user who created the book so every member
+/// gets the same totals. A rate of the opposite pair is inverted. The amount is NULL when there's
+/// no such rate.
+fn push_movements(
+    builder: &mut QueryBuilder<'_, Postgres>,
+    user_id: Uuid,
+    period: Period,
+    filter: &TicketFilter,
+) {
+    builder
+        .push(
+            r#"movements AS (
+            SELECT
+                date_trunc("#,
+        )
+        .push_bind(period.as_str())
+        .push(
+            r#", amt.created_at, 'UTC') as period,
+                amt.id,
+                amt.type_id,
+                amt.direction,
+                ta.currency,
+                CASE
+                    WHEN amt.currency = ta.currency THEN amt.amount
+                    ELSE ROUND(amt.amount * fx.rate, tc.minor_units)
+                END as amount
+            FROM movement_line amt
+              INNER JOIN tbl_accounting ta ON ta.id = amt.accounting_id
+              INNER JOIN tbl_accounting_member tam ON tam.accounting_id = ta.id
+              INNER JOIN tbl_currency tc ON tc.code = ta.currency
+              LEFT JOIN LATERAL (
+                  SELECT CASE WHEN er.base = amt.currency THEN er.rate ELSE 1 / er.rate END as rate
+                  FROM tbl_exchange_rate er
+                  WHERE
+                      er.user_id = ta.user_id
+                      AND (
+                          (er.base = amt.currency AND er.quote = ta.currency)
+                          OR (er.base = ta.currency AND er.quote = amt.currency)
+                      )
+                      AND er.day <= (amt.created_at AT TIME ZONE 'UTC')::date
+                  ORDER BY er.day DESC, er.base = amt.currency DESC
+                  LIMIT 1
+              ) fx ON amt.currency <> ta.currency
+            WHERE "#,
+        );
+    filter.push_line_conditions(builder, user_id);
+    builder.push(")");
+}
+
+/// Totals per period, in the currency of the books. Books with different currencies get a row
+/// each.
+pub async fn period_totals(
+    pool: &PgPool,
+    user_id: Uuid,
+    period: Period,
+    filter: &TicketFilter,
+) -> Result<Vec<PeriodTotal>, sqlx::Error> {
+    let mut builder: QueryBuilder<Postgres> = QueryBuilder::new("WITH ");
+    push_movements(&mut builder, user_id, period, filter);
+    builder
+        .push("SELECT m.period, m.currency,")
+        .push(TOTALS)
+        .push(" FROM movements m GROUP BY m.period, m.currency ORDER BY m.period, m.currency");
+    builder.build_query_as().fetch_all(pool).await
+}
+
+/// Totals per period and category, every category also counts the movements of its children.
+///
+/// Like `period_totals`, books of different currencies are summed separately.
+pub async fn category_totals(
+    pool: &PgPool,
+    user_id: Uuid,
+    period: Period,
+    filter: &TicketFilter,
+) -> Result<Vec<CategoryTotal>, sqlx::Error> {
+    let mut builder: QueryBuilder<Postgres> = QueryBuilder::new(
+        r#"WITH RECURSIVE closure AS (
+            SELECT id as ancestor_id, id as type_id FROM tbl_type
+            UNION ALL
+            SELECT c.ancestor_id, tt.id
+            FROM closure c
+              INNER JOIN tbl_type tt ON tt.parent_id = c.type_id
+        ), "#,
+    );
+    push_movements(&mut builder, user_id, period, filter);
+    builder
+        .push(
+            r#"SELECT
+                m.period,
+                m.currency,
+                tt.id as type_id,
+                tt.parent_id,
+                tt.name,"#,
+        )
+        .push(TOTALS)
+        .push(
+            r#"
+            FROM movements m
+              INNER JOIN closure c ON c.type_id = m.type_id
+              INNER JOIN tbl_type tt ON tt.id = c.ancestor_id
+            GROUP BY m.period, m.currency, tt.id, tt.parent_id, tt.name
+            ORDER BY m.period, m.currency, tt.name"#,
+        );
+    builder.build_query_as().fetch_all(pool).await
+}
+
+/// Totals per period and tag, a ticket with several tags counts in each of them and untagged
+/// tickets are left out.
+pub async fn tag_totals(
+    pool: &PgPool,
+    user_id: Uuid,
+    period: Period,
+    filter: &TicketFilter,
+) -> Result<Vec<TagTotal>, sqlx::Error> {
+    let mut builder: QueryBuilder<Postgres> = QueryBuilder::new("WITH ");
+    push_movements(&mut builder, user_id, period, filter);
+    builder
+        .push(
+            r#"SELECT
+                m.period,
+                m.currency,
+                tg.id as tag_id,
+                tg.name,"#,
+        )
+        .push(TOTALS)
+        .push(
+            r#"
+            FROM movements m
+              INNER JOIN tbl_movement_tag mt ON mt.movement_id = m.id
+              INNER JOIN tbl_tag tg ON tg.id = mt.tag_id AND tg.user_id = "#,
+        )
+        .push_bind(user_id)
+        .push(
+            r#"
+            GROUP BY m.period, m.currency, tg.id, tg.name
+            ORDER BY m.period, m.currency, tg.name"#,
+        );
+    builder.build_query_as().fetch_all(pool).await
+}
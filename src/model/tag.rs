@@ -0,0 +1,207 @@
+//! Free-form labels on tickets, like "reimbursable" or "trip-2026-lisbon", which unlike
+//! categories can cut across them and a ticket can carry several of.
+
+use crate::model::ticket::TicketError;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+/// Tags a single ticket can carry.
+const MAX_TICKET_TAGS: usize = 20;
+
+#[derive(Debug, serde::Serialize)]
+pub struct Tag {
+    pub id: Uuid,
+    pub name: String,
+    /// Number of tickets carrying the tag.
+    pub tickets: i64,
+}
+
+/// Tags are matched case insensitively, so they're kept lower case with their inner spaces
+/// collapsed.
+pub fn clean_name(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+pub fn normalize_name(name: &str) -> Result<String, &'static str> {
+    let name = clean_name(name);
+    if name.is_empty() {
+        return Err("tag can't be empty");
+    }
+    if name.contains(',') {
+        return Err("tag can't contain a comma");
+    }
+    if name.chars().count() > 50 {
+        return Err("tag can have at most 50 characters");
+    }
+    Ok(name)
+}
+
+/// The tags of the user by name, with how many tickets carry them.
+pub async fn list(pool: &PgPool, user_id: Uuid) -> Result<Vec<Tag>, sqlx::Error> {
+    sqlx::query_as!(
+        Tag,
+        r#"SELECT tg.id, tg.name, COUNT(mt.movement_id) as "tickets!"
+        FROM tbl_tag tg
+          LEFT JOIN tbl_movement_tag mt ON mt.tag_id = tg.id
+        WHERE
+            tg.user_id = $1
+        GROUP BY tg.id, tg.name
+        ORDER BY tg.name"#,
+        user_id,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Names of the tags containing `text` for autocompletion, the ones starting with it first,
+/// then the most used.
+pub async fn suggest(pool: &PgPool, user_id: Uuid, text: &str) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT tg.name
+        FROM tbl_tag tg
+        WHERE
+            tg.user_id = $1 AND strpos(tg.name, $2) > 0
+        ORDER BY
+            starts_with(tg.name, $2) DESC,
+            (SELECT COUNT(*) FROM tbl_movement_tag mt WHERE mt.tag_id = tg.id) DESC,
+            tg.name
+        LIMIT 10"#,
+        user_id,
+        clean_name(text),
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Replace the tags of a ticket, creating the ones the user doesn't have yet. The caller checks
+/// the ticket belongs to the user.
+pub(crate) async fn replace_ticket_tags(
+    connection: &mut PgConnection,
+    user_id: Uuid,
+    ticket_id: Uuid,
+    names: &[String],
+) -> Result<(), TicketError> {
+    let mut tags: Vec<String> = vec![];
+    for name in names {
+        let name = normalize_name(name).map_err(TicketError::Invalid)?;
+        if !tags.contains(&name) {
+            tags.push(name);
+        }
+    }
+    if tags.len() > MAX_TICKET_TAGS {
+        return Err(TicketError::Invalid("a ticket can have at most 20 tags"));
+    }
+    sqlx::query!(
+        r#"INSERT INTO tbl_tag(user_id, name)
+        SELECT $1, * FROM UNNEST($2::text[])
+        ON CONFLICT (user_id, name) DO NOTHING"#,
+        user_id,
+        &tags,
+    )
+    .execute(&mut *connection)
+    .await?;
+    // the tags other members of a shared book put on the ticket are theirs to change
+    sqlx::query!(
+        r#"DELETE FROM tbl_movement_tag mt
+        USING tbl_tag tg
+        WHERE tg.id = mt.tag_id AND mt.movement_id = $1 AND tg.user_id = $2"#,
+        ticket_id,
+        user_id,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        r#"INSERT INTO tbl_movement_tag(movement_id, tag_id)
+        SELECT $1, tg.id FROM tbl_tag tg WHERE tg.user_id = $2 AND tg.name = ANY($3)"#,
+        ticket_id,
+        user_id,
+        &tags,
+    )
+    .execute(&mut *connection)
+    .await?;
+    Ok(())
+}
+
+/// Rename a tag, merging it is the way to give it the name of another tag.
+pub async fn rename(
+    pool: &PgPool,
+    user_id: Uuid,
+    tag_id: Uuid,
+    name: &str,
+) -> Result<(), TicketError> {
+    let name = normalize_name(name).map_err(TicketError::Invalid)?;
+    let result = sqlx::query!(
+        "UPDATE tbl_tag SET name = $3 WHERE id = $1 AND user_id = $2",
+        tag_id,
+        user_id,
+        name,
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db_error) if db_error.is_unique_violation() => {
+            TicketError::Invalid("a tag with this name already exists, merge them instead")
+        }
+        _ => e.into(),
+    })?;
+    if result.rows_affected() == 0 {
+        return Err(TicketError::NotFound);
+    }
+    Ok(())
+}
+
+/// Move the tickets of `tag_id` to `into_id` and delete `tag_id`.
+pub async fn merge(
+    pool: &PgPool,
+    user_id: Uuid,
+    tag_id: Uuid,
+    into_id: Uuid,
+) -> Result<(), TicketError> {
+    if tag_id == into_id {
+        return Err(TicketError::Invalid("can't merge a tag into itself"));
+    }
+    let mut transaction = pool.begin().await?;
+    let found = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM tbl_tag WHERE user_id = $1 AND id IN ($2, $3)"#,
+        user_id,
+        tag_id,
+        into_id,
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+    if found != 2 {
+        return Err(TicketError::NotFound);
+    }
+    sqlx::query!(
+        r#"INSERT INTO tbl_movement_tag(movement_id, tag_id)
+        SELECT movement_id, $2 FROM tbl_movement_tag WHERE tag_id = $1
+        ON CONFLICT DO NOTHING"#,
+        tag_id,
+        into_id,
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!("DELETE FROM tbl_tag WHERE id = $1", tag_id)
+        .execute(&mut *transaction)
+        .await?;
+    transaction.commit().await?;
+    Ok(())
+}
+
+/// Delete a tag, the tickets carrying it only lose the tag.
+pub async fn delete(pool: &PgPool, user_id: Uuid, tag_id: Uuid) -> Result<(), TicketError> {
+    let result = sqlx::query!(
+        "DELETE FROM tbl_tag WHERE id = $1 AND user_id = $2",
+        tag_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(TicketError::NotFound);
+    }
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum TicketError {
+    #[error("{0}")]
+    Invalid(&'static str),
+    #[error("ticket not found")]
+    NotFound,
+    /// The user is a member of the book, but their role doesn't allow the operation.
+    #[error("not allowed in this book")]
+    Forbidden,
+    #[error(transparent)]
+    UnexpectedError(#[from] sqlx::Error),
+}
+
+impl IntoResponse for TicketError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Invalid(message) => (StatusCode::BAD_REQUEST, message).into_response(),
+            Self::NotFound => StatusCode::NOT_FOUND.into_response(),
+            Self::Forbidden => (StatusCode::FORBIDDEN, self.to_string()).into_response(),
+            Self::UnexpectedError(e) => {
+                tracing::error!("Failed ticket operation: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use super::filter::TicketFilter;
+use crate::model::direction::TicketDirection;
+use sqlx::{types::Decimal, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+/// A ticket as written to an export, the amount keeps its exact decimal value.
+#[derive(Debug, serde::Serialize, sqlx::FromRow)]
+pub struct ExportRow {
+    pub id: Uuid,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub accounting: String,
+    pub direction: TicketDirection,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+    pub currency: String,
+    /// Names from the root category down to the ticket's one, joined by " / ".
+    pub category: String,
+    pub description: String,
+    /// Names of the tags of the ticket, joined by ", ".
+    pub tags: String,
+}
+
+/// The tickets matching `filter`, oldest first, meant to be streamed with `fetch`.
+pub fn query(user_id: Uuid, filter: &TicketFilter) -> QueryBuilder<'static, Postgres> {
+    let mut builder = QueryBuilder::new(
+        r#"WITH RECURSIVE paths AS (
+            SELECT id, name::text AS path FROM tbl_type WHERE parent_id IS NULL
+            UNION ALL
+            SELECT tt.id, paths.path || ' / ' || tt.name
+            FROM tbl_type tt
+              INNER JOIN paths ON tt.parent_id = paths.id
+        )
+        SELECT
+            amt.id,
+            amt.created_at,
+            ta.name as accounting,
+            amt.direction,
+            ROUND(amt.amount, tc.minor_units) as amount,
+            amt.currency,
+            paths.path as category,
+            amt.description,
+            ARRAY_TO_STRING(ARRAY(
+                SELECT tg.name
+                FROM tbl_movement_tag mt
+                  INNER JOIN tbl_tag tg ON tg.id = mt.tag_id
+                WHERE mt.movement_id = amt.id AND tg.user_id = tam.user_id
+                ORDER BY tg.name
+            ), ', ') as tags
+        FROM accounting_movement_tbl amt
+          INNER JOIN tbl_accounting ta ON ta.id = amt.accounting_id
+          INNER JOIN tbl_accounting_member tam ON tam.accounting_id = ta.id
+          INNER JOIN tbl_currency tc ON tc.code = amt.currency
+          INNER JOIN paths ON paths.id = amt.type_id
+        WHERE "#,
+    );
+    filter.push_conditions(&mut builder, user_id);
+    builder.push(" ORDER BY amt.created_at, amt.id");
+    builder
+}
@@ -0,0 +1,135 @@
+use crate::model::{direction::TicketDirection, tag};
+use sqlx::{types::Decimal, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+/// Conditions shared by every query listing the tickets of a user.
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct TicketFilter {
+    pub accounting_id: Option<Uuid>,
+    /// Inclusive lower bound on `created_at`.
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    /// Exclusive upper bound on `created_at`.
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    pub direction: Option<TicketDirection>,
+    pub type_id: Option<Uuid>,
+    /// Also match the tickets filed under any child of `type_id`.
+    #[serde(default)]
+    pub include_subtypes: bool,
+    pub min_amount: Option<Decimal>,
+    pub max_amount: Option<Decimal>,
+    /// Case insensitive match anywhere in the description.
+    pub description: Option<String>,
+    /// Only the tickets carrying the tag of this name.
+    pub tag: Option<String>,
+    /// Whether transfers between books are included, by default the ticket list includes them
+    /// and reports don't.
+    pub transfers: Option<bool>,
+}
+
+impl TicketFilter {
+    /// Append the conditions to a query over `accounting_movement_tbl amt` joined with
+    /// `tbl_accounting ta` and its members `tbl_accounting_member tam`, starting right after its
+    /// `WHERE`. Only the books the user is a member of are matched.
+    ///
+    /// A split ticket matches the category of any of its lines.
+    pub fn push_conditions(&self, builder: &mut QueryBuilder<'_, Postgres>, user_id: Uuid) {
+        self.push(builder, user_id, false);
+    }
+
+    /// Same as `push_conditions` over `movement_line amt` for reports, where each line of a
+    /// split ticket is matched on its own category and transfers are left out unless asked for.
+    pub fn push_line_conditions(&self, builder: &mut QueryBuilder<'_, Postgres>, user_id: Uuid) {
+        self.push(builder, user_id, true);
+    }
+
+    /// The ids matched by `type_id`, as a parenthesized list or subquery.
+    fn push_type_ids(&self, builder: &mut QueryBuilder<'_, Postgres>, type_id: Uuid) {
+        if self.include_subtypes {
+            builder
+                .push(
+                    r#"(
+                        WITH RECURSIVE subtree AS (
+                            SELECT id FROM tbl_type WHERE id = "#,
+                )
+                .push_bind(type_id)
+                .push(
+                    r#"
+                            UNION
+                            SELECT tt.id FROM tbl_type tt INNER JOIN subtree s ON tt.parent_id = s.id
+                        )
+                        SELECT id FROM subtree
+                    )"#,
+                );
+        } else {
+            builder.push("(").push_bind(type_id).push(")");
+        }
+    }
+
+    fn push(&self, builder: &mut QueryBuilder<'_, Postgres>, user_id: Uuid, lines: bool) {
+        builder.push("tam.user_id = ").push_bind(user_id);
+        if let Some(accounting_id) = self.accounting_id {
+            builder.push(" AND ta.id = ").push_bind(accounting_id);
+        }
+        if let Some(from) = self.from {
+            builder.push(" AND amt.created_at >= ").push_bind(from);
+        }
+        if let Some(to) = self.to {
+            builder.push(" AND amt.created_at < ").push_bind(to);
+        }
+        if let Some(direction) = &self.direction {
+            builder
+                .push(" AND amt.direction = ")
+                .push_bind(direction.clone());
+        }
+        if let Some(type_id) = self.type_id {
+            builder.push(" AND (amt.type_id IN ");
+            self.push_type_ids(builder, type_id);
+            if !lines {
+                builder.push(
+                    " OR EXISTS (SELECT 1 FROM tbl_split ts WHERE ts.movement_id = amt.id AND ts.type_id IN ",
+                );
+                self.push_type_ids(builder, type_id);
+                builder.push(")");
+            }
+            builder.push(")");
+        }
+        if !self.transfers.unwrap_or(!lines) {
+            builder.push(" AND amt.transfer_id IS NULL");
+        }
+        if let Some(min_amount) = self.min_amount {
+            builder.push(" AND amt.amount >= ").push_bind(min_amount);
+        }
+        if let Some(max_amount) = self.max_amount {
+            builder.push(" AND amt.amount <= ").push_bind(max_amount);
+        }
+        if let Some(description) = self.description.as_deref().filter(|d| !d.is_empty()) {
+            builder
+                .push(" AND amt.description ILIKE ")
+                .push_bind(like_pattern(description));
+        }
+        if let Some(name) = self
+            .tag
+            .as_deref()
+            .map(tag::clean_name)
+            .filter(|n| !n.is_empty())
+        {
+            builder
+                .push(
+                    r#" AND EXISTS (
+                        SELECT 1 FROM tbl_movement_tag mt INNER JOIN tbl_tag tg ON tg.id = mt.tag_id
+                        WHERE mt.movement_id = amt.id AND tg.user_id = tam.user_id AND tg.name = "#,
+                )
+                .push_bind(name)
+                .push(")");
+        }
+    }
+}
+
+/// Escape the `LIKE` wildcards so the text is matched literally.
+fn like_pattern(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{escaped}%")
+}
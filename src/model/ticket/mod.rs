@@ -0,0 +1,369 @@
+mod error;
+pub mod export;
+pub mod filter;
+pub mod pagination;
+pub mod split;
+pub mod validation;
+
+pub use error::TicketError;
+
+use crate::model::{
+    currency::normalize_code,
+    direction::TicketDirection,
+    member::{book_role, forbid_viewer, require_write, ticket_role},
+    tag, transfer,
+};
+use filter::TicketFilter;
+use pagination::{Cursor, TicketPagination};
+use sqlx::{types::Decimal, PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
+use validation::{is_usable_type, ticket_minor_units, user_error, validate_amount};
+
+#[derive(Debug, serde::Serialize, sqlx::FromRow)]
+pub struct Ticket {
+    pub id: Uuid,
+    pub accounting_id: Uuid,
+    pub direction: TicketDirection,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
+    pub currency: String,
+    #[sqlx(rename = "type")]
+    pub r#type: String,
+    pub type_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub description: String,
+    /// Whether the amount is split into lines, see `split::lines`.
+    pub split: bool,
+    /// Names of the tags of the ticket, in alphabetical order.
+    pub tags: Vec<String>,
+    /// Shared by both sides of a transfer between books, see `transfer::get`.
+    pub transfer_id: Option<Uuid>,
+    /// Username of the member who created the ticket, missing once their account is gone.
+    pub created_by: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct TicketList {
+    pub tickets: Vec<Ticket>,
+    /// Pass it back as `cursor` to get the following page, missing on the last page.
+    pub next_cursor: Option<String>,
+}
+
+/// The fields of a ticket a user can write.
+#[derive(Debug, serde::Deserialize)]
+pub struct TicketInput {
+    pub direction: TicketDirection,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
+    pub type_id: Uuid,
+    pub description: String,
+    /// Defaults to now on creation, left untouched on update.
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Defaults to the user's current book on creation, left untouched on update.
+    pub accounting_id: Option<Uuid>,
+    /// Defaults to the currency of the book on creation, left untouched on update.
+    pub currency: Option<String>,
+    /// Replace the tags of the ticket, left untouched on update when missing.
+    pub tags: Option<Vec<String>>,
+}
+
+const SELECT_TICKET: &str = r#"SELECT
+        amt.id,
+        amt.accounting_id,
+        amt.direction,
+        ROUND(amt.amount, tc.minor_units) as amount,
+        amt.currency,
+        amt.description,
+        amt.created_at,
+        tt.name as type,
+        tt.id as type_id,
+        tt.parent_id,
+        EXISTS (SELECT 1 FROM tbl_split ts WHERE ts.movement_id = amt.id) as split,
+        ARRAY(
+            SELECT tg.name
+            FROM tbl_movement_tag mt
+              INNER JOIN tbl_tag tg ON tg.id = mt.tag_id
+            WHERE mt.movement_id = amt.id AND tg.user_id = tam.user_id
+            ORDER BY tg.name
+        ) as tags,
+        amt.transfer_id,
+        cb.username as created_by
+    FROM accounting_movement_tbl amt
+      INNER JOIN tbl_type tt ON tt.id = amt.type_id
+      INNER JOIN tbl_currency tc ON tc.code = amt.currency
+      INNER JOIN tbl_accounting ta ON ta.id = amt.accounting_id
+      INNER JOIN tbl_accounting_member tam ON tam.accounting_id = ta.id
+      LEFT JOIN tbl_user cb ON cb.id = amt.created_by
+    WHERE "#;
+
+async fn validate(
+    pool: &PgPool,
+    user_id: Uuid,
+    input: &TicketInput,
+    ticket_id: Option<Uuid>,
+) -> Result<(), TicketError> {
+    if let Some(ticket_id) = ticket_id {
+        require_write(ticket_role(pool, user_id, ticket_id).await?)?;
+    }
+    if ticket_id.is_none() || input.accounting_id.is_some() {
+        forbid_viewer(book_role(pool, user_id, input.accounting_id).await?)?;
+    }
+    let currency = input.currency.as_deref().map(normalize_code);
+    let minor_units = match ticket_minor_units(
+        pool,
+        user_id,
+        currency.as_deref(),
+        input.accounting_id,
+        ticket_id,
+    )
+    .await?
+    {
+        Some(minor_units) => minor_units,
+        None if currency.is_some() => return Err(TicketError::Invalid("unknown currency")),
+        // the missing book or ticket is reported by the write itself
+        None => 2,
+    };
+    validate_amount(&input.amount, minor_units).map_err(TicketError::Invalid)?;
+    if let Some(ticket_id) = ticket_id {
+        if let Some((direction, peer_accounting_id)) =
+            transfer::peer(pool, user_id, ticket_id).await?
+        {
+            if direction != input.direction {
+                return Err(TicketError::Invalid(
+                    "the direction of a transfer can't be changed",
+                ));
+            }
+            if input.accounting_id == Some(peer_accounting_id) {
+                return Err(TicketError::Invalid("a transfer needs two different books"));
+            }
+            require_peer_write(pool, user_id, peer_accounting_id).await?;
+        }
+        let lines = split::line_amounts(pool, user_id, ticket_id).await?;
+        if !lines.is_empty() {
+            if lines.iter().sum::<Decimal>() != input.amount {
+                return Err(TicketError::Invalid(
+                    "amount must equal the sum of the split lines",
+                ));
+            }
+            for line in &lines {
+                validate_amount(line, minor_units).map_err(TicketError::Invalid)?;
+            }
+        }
+    }
+    if !is_usable_type(pool, user_id, input.type_id, ticket_id).await? {
+        return Err(TicketError::Invalid("unknown type_id"));
+    }
+    Ok(())
+}
+
+/// Both sides of a transfer change together, so the book of the other side must be writable too.
+async fn require_peer_write(
+    pool: &PgPool,
+    user_id: Uuid,
+    peer_accounting_id: Uuid,
+) -> Result<(), TicketError> {
+    match book_role(pool, user_id, Some(peer_accounting_id)).await? {
+        Some(role) if role.can_write() => Ok(()),
+        _ => Err(TicketError::Forbidden),
+    }
+}
+
+fn map_write_error(error: sqlx::Error) -> TicketError {
+    match user_error(&error) {
+        Some(message) => TicketError::Invalid(message),
+        None => error.into(),
+    }
+}
+
+pub async fn get(pool: &PgPool, user_id: Uuid, ticket_id: Uuid) -> Result<Ticket, TicketError> {
+    let mut builder: QueryBuilder<Postgres> = QueryBuilder::new(SELECT_TICKET);
+    builder
+        .push("tam.user_id = ")
+        .push_bind(user_id)
+        .push(" AND amt.id = ")
+        .push_bind(ticket_id);
+    builder
+        .build_query_as()
+        .fetch_optional(pool)
+        .await?
+        .ok_or(TicketError::NotFound)
+}
+
+pub async fn list(
+    pool: &PgPool,
+    user_id: Uuid,
+    filter: &TicketFilter,
+    pagination: &TicketPagination,
+) -> Result<TicketList, TicketError> {
+    let cursor = pagination
+        .cursor()
+        .map_err(|_| TicketError::Invalid("invalid cursor"))?;
+    let mut builder: QueryBuilder<Postgres> = QueryBuilder::new(SELECT_TICKET);
+    filter.push_conditions(&mut builder, user_id);
+    pagination.push_page(&mut builder, cursor);
+    let mut tickets: Vec<Ticket> = builder.build_query_as().fetch_all(pool).await?;
+    let limit = pagination.limit() as usize;
+    let next_cursor = if tickets.len() > limit {
+        tickets.truncate(limit);
+        tickets.last().map(|ticket| {
+            Cursor::new(pagination.sort, ticket.created_at, ticket.amount, ticket.id).to_string()
+        })
+    } else {
+        None
+    };
+    Ok(TicketList {
+        tickets,
+        next_cursor,
+    })
+}
+
+pub async fn create(
+    pool: &PgPool,
+    user_id: Uuid,
+    input: &TicketInput,
+) -> Result<Uuid, TicketError> {
+    validate(pool, user_id, input, None).await?;
+    let mut transaction = pool.begin().await?;
+    let row = sqlx::query!(
+        r#"INSERT INTO accounting_movement_tbl(accounting_id,type_id,direction,amount,description,created_at,currency,created_by)
+        SELECT ta.id,$1,$2,ROUND($3, tc.minor_units),$4,$5,tc.code,$6
+        FROM tbl_accounting ta
+          INNER JOIN tbl_currency tc ON tc.code = COALESCE($8, ta.currency)
+        WHERE
+          EXISTS (
+            SELECT 1 FROM tbl_accounting_member tam
+            WHERE tam.accounting_id = ta.id AND tam.user_id = $6 AND tam.role <> 'viewer'
+          )
+          AND ta.id = COALESCE($7, (SELECT current_accounting_id FROM tbl_user WHERE id = $6))
+        RETURNING id"#,
+        input.type_id,
+        input.direction.clone() as TicketDirection,
+        input.amount,
+        input.description,
+        input.created_at.unwrap_or(chrono::Utc::now()),
+        user_id,
+        input.accounting_id,
+        input.currency.as_deref().map(normalize_code),
+    )
+    .fetch_optional(&mut *transaction)
+    .await
+    .map_err(map_write_error)?;
+    let ticket_id = match row {
+        Some(row) => row.id,
+        None if input.accounting_id.is_some() => {
+            return Err(TicketError::Invalid("unknown accounting_id"))
+        }
+        None => return Err(TicketError::Invalid("no accounting selected")),
+    };
+    if let Some(tags) = &input.tags {
+        tag::replace_ticket_tags(&mut transaction, user_id, ticket_id, tags).await?;
+    }
+    transaction.commit().await?;
+    Ok(ticket_id)
+}
+
+pub async fn update(
+    pool: &PgPool,
+    user_id: Uuid,
+    ticket_id: Uuid,
+    input: &TicketInput,
+) -> Result<(), TicketError> {
+    validate(pool, user_id, input, Some(ticket_id)).await?;
+    let mut transaction = pool.begin().await?;
+    let result = sqlx::query!(
+        r#"UPDATE accounting_movement_tbl amt
+        SET
+            type_id = $1,
+            direction = $2,
+            amount = ROUND($3, tc.minor_units),
+            currency = tc.code,
+            description = $4,
+            created_at = COALESCE($5, amt.created_at),
+            accounting_id = COALESCE($8, amt.accounting_id)
+        FROM tbl_accounting ta, tbl_currency tc
+        WHERE
+            ta.id = amt.accounting_id AND amt.id = $6
+            AND EXISTS (
+                SELECT 1 FROM tbl_accounting_member tam
+                WHERE tam.accounting_id = ta.id AND tam.user_id = $7 AND tam.role <> 'viewer'
+            )
+            AND tc.code = COALESCE($9, amt.currency)
+            AND ($8::uuid IS NULL OR EXISTS (
+                SELECT 1 FROM tbl_accounting_member
+                WHERE accounting_id = $8 AND user_id = $7 AND role <> 'viewer'
+            ))"#,
+        input.type_id,
+        input.direction.clone() as TicketDirection,
+        input.amount,
+        input.description,
+        input.created_at,
+        ticket_id,
+        user_id,
+        input.accounting_id,
+        input.currency.as_deref().map(normalize_code),
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(map_write_error)?;
+    if result.rows_affected() == 0 {
+        return Err(TicketError::NotFound);
+    }
+    transfer::sync_peer(&mut transaction, ticket_id).await?;
+    if let Some(tags) = &input.tags {
+        tag::replace_ticket_tags(&mut transaction, user_id, ticket_id, tags).await?;
+    }
+    transaction.commit().await?;
+    Ok(())
+}
+
+/// Delete a ticket with its attachments, along with the other side when it's a transfer. Returns
+/// the keys of the attachments content so the caller can remove it from the blob store once the
+/// ticket is gone.
+pub async fn delete(
+    pool: &PgPool,
+    user_id: Uuid,
+    ticket_id: Uuid,
+) -> Result<Vec<String>, TicketError> {
+    require_write(ticket_role(pool, user_id, ticket_id).await?)?;
+    if let Some((_, peer_accounting_id)) = transfer::peer(pool, user_id, ticket_id).await? {
+        require_peer_write(pool, user_id, peer_accounting_id).await?;
+    }
+    let mut transaction = pool.begin().await?;
+    let storage_keys = sqlx::query!(
+        r#"DELETE FROM tbl_attachment at
+        USING accounting_movement_tbl amt, tbl_accounting_member tam
+        WHERE
+            amt.id = at.movement_id AND tam.accounting_id = amt.accounting_id
+            AND tam.user_id = $2 AND tam.role <> 'viewer'
+            AND (amt.id = $1 OR amt.transfer_id = (
+                SELECT transfer_id FROM accounting_movement_tbl WHERE id = $1
+            ))
+        RETURNING at.storage_key"#,
+        ticket_id,
+        user_id,
+    )
+    .fetch_all(&mut *transaction)
+    .await?
+    .into_iter()
+    .map(|row| row.storage_key)
+    .collect();
+    let result = sqlx::query!(
+        r#"DELETE FROM accounting_movement_tbl amt
+        USING tbl_accounting_member tam
+        WHERE
+            tam.accounting_id = amt.accounting_id AND tam.user_id = $2 AND tam.role <> 'viewer'
+            AND (amt.id = $1 OR amt.transfer_id = (
+                SELECT transfer_id FROM accounting_movement_tbl WHERE id = $1
+            ))"#,
+        ticket_id,
+        user_id,
+    )
+    .execute(&mut *transaction)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(TicketError::NotFound);
+    }
+    transaction.commit().await?;
+    Ok(storage_keys)
+}
@@ -0,0 +1,146 @@
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, SecondsFormat, Utc};
+use sqlx::{types::Decimal, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 200;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TicketSort {
+    #[default]
+    Date,
+    Amount,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct TicketPagination {
+    #[serde(default)]
+    pub sort: TicketSort,
+    #[serde(default)]
+    pub order: SortOrder,
+    /// `next_cursor` of the previous page.
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Position of the last ticket of a page: its sort key and id, the id breaks ties so the order
+/// is total and rows are never skipped or repeated between pages.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cursor {
+    Date(DateTime<Utc>, Uuid),
+    Amount(Decimal, Uuid),
+}
+
+impl Cursor {
+    pub fn parse(value: &str, sort: TicketSort) -> anyhow::Result<Self> {
+        let (key, id) = value
+            .rsplit_once('_')
+            .ok_or_else(|| anyhow!("cursor '{value}' has no id"))?;
+        let id = Uuid::parse_str(id).context("invalid cursor id")?;
+        match sort {
+            TicketSort::Date => Ok(Self::Date(
+                DateTime::parse_from_rfc3339(key)
+                    .context("invalid cursor date")?
+                    .with_timezone(&Utc),
+                id,
+            )),
+            TicketSort::Amount => Ok(Self::Amount(
+                key.parse().context("invalid cursor amount")?,
+                id,
+            )),
+        }
+    }
+
+    pub fn new(sort: TicketSort, created_at: DateTime<Utc>, amount: Decimal, id: Uuid) -> Self {
+        match sort {
+            TicketSort::Date => Self::Date(created_at, id),
+            TicketSort::Amount => Self::Amount(amount, id),
+        }
+    }
+}
+
+impl std::fmt::Display for Cursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Date(created_at, id) => write!(
+                f,
+                "{}_{id}",
+                created_at.to_rfc3339_opts(SecondsFormat::Micros, true)
+            ),
+            Self::Amount(amount, id) => write!(f, "{amount}_{id}"),
+        }
+    }
+}
+
+impl TicketPagination {
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+
+    pub fn cursor(&self) -> anyhow::Result<Option<Cursor>> {
+        self.cursor
+            .as_deref()
+            .map(|cursor| Cursor::parse(cursor, self.sort))
+            .transpose()
+    }
+
+    fn column(&self) -> &'static str {
+        match self.sort {
+            TicketSort::Date => "amt.created_at",
+            TicketSort::Amount => "amt.amount",
+        }
+    }
+
+    fn direction(&self) -> &'static str {
+        match self.order {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+
+    /// Append the cursor condition, the ordering and the limit after the `WHERE` conditions.
+    ///
+    /// One row more than the limit is requested, its presence tells there is a next page.
+    pub fn push_page(&self, builder: &mut QueryBuilder<'_, Postgres>, cursor: Option<Cursor>) {
+        let column = self.column();
+        let comparison = match self.order {
+            SortOrder::Asc => ">",
+            SortOrder::Desc => "<",
+        };
+        match cursor {
+            Some(Cursor::Date(created_at, id)) => {
+                builder
+                    .push(format!(" AND ({column}, amt.id) {comparison} ("))
+                    .push_bind(created_at)
+                    .push(", ")
+                    .push_bind(id)
+                    .push(")");
+            }
+            Some(Cursor::Amount(amount, id)) => {
+                builder
+                    .push(format!(" AND ({column}, amt.id) {comparison} ("))
+                    .push_bind(amount)
+                    .push(", ")
+                    .push_bind(id)
+                    .push(")");
+            }
+            None => {}
+        }
+        let direction = self.direction();
+        builder
+            .push(format!(
+                " ORDER BY {column} {direction}, amt.id {direction} LIMIT "
+            ))
+            .push_bind(self.limit() + 1);
+    }
+}
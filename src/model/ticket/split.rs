@@ -0,0 +1,145 @@
+//! A ticket split into lines, each with its own category, amount and note. The lines add up to
+//! the amount of the ticket, and reports count them instead of the ticket.
+
+use super::{
+    validation::{is_usable_type, validate_amount},
+    TicketError,
+};
+use crate::model::member::{require_write, ticket_role};
+use sqlx::{types::Decimal, PgPool};
+use uuid::Uuid;
+
+#[derive(Debug, serde::Serialize)]
+pub struct SplitLine {
+    pub type_id: Uuid,
+    #[serde(rename = "type")]
+    pub r#type: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
+    pub note: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct SplitLineInput {
+    pub type_id: Uuid,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
+    #[serde(default)]
+    pub note: String,
+}
+
+/// The lines of a ticket in their order, empty when it isn't split.
+pub async fn lines(
+    pool: &PgPool,
+    user_id: Uuid,
+    ticket_id: Uuid,
+) -> Result<Vec<SplitLine>, TicketError> {
+    let lines = sqlx::query_as!(
+        SplitLine,
+        r#"SELECT ts.type_id, tt.name as type, ts.amount, ts.note
+        FROM tbl_split ts
+          INNER JOIN tbl_type tt ON tt.id = ts.type_id
+          INNER JOIN accounting_movement_tbl amt ON amt.id = ts.movement_id
+          INNER JOIN tbl_accounting_member tam ON tam.accounting_id = amt.accounting_id
+        WHERE
+            amt.id = $1 AND tam.user_id = $2
+        ORDER BY ts.position"#,
+        ticket_id,
+        user_id,
+    )
+    .fetch_all(pool)
+    .await?;
+    if lines.is_empty() {
+        // tell a ticket without lines from a missing one
+        super::get(pool, user_id, ticket_id).await?;
+    }
+    Ok(lines)
+}
+
+/// Replace the lines of a ticket, an empty list removes the split.
+pub async fn replace(
+    pool: &PgPool,
+    user_id: Uuid,
+    ticket_id: Uuid,
+    lines: &[SplitLineInput],
+) -> Result<(), TicketError> {
+    if lines.len() == 1 {
+        return Err(TicketError::Invalid("a split needs at least 2 lines"));
+    }
+    require_write(ticket_role(pool, user_id, ticket_id).await?)?;
+    let mut transaction = pool.begin().await?;
+    // the lock keeps the amount from changing until the lines are written
+    let ticket = sqlx::query!(
+        r#"SELECT amt.amount, amt.transfer_id, tc.minor_units
+        FROM accounting_movement_tbl amt
+          INNER JOIN tbl_accounting_member tam ON tam.accounting_id = amt.accounting_id
+          INNER JOIN tbl_currency tc ON tc.code = amt.currency
+        WHERE
+            amt.id = $1 AND tam.user_id = $2 AND tam.role <> 'viewer'
+        FOR UPDATE OF amt"#,
+        ticket_id,
+        user_id,
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    .ok_or(TicketError::NotFound)?;
+    if ticket.transfer_id.is_some() && !lines.is_empty() {
+        return Err(TicketError::Invalid("a transfer can't be split"));
+    }
+    let mut total = Decimal::ZERO;
+    for line in lines {
+        validate_amount(&line.amount, ticket.minor_units as u32).map_err(TicketError::Invalid)?;
+        if !is_usable_type(pool, user_id, line.type_id, Some(ticket_id)).await? {
+            return Err(TicketError::Invalid("unknown type_id"));
+        }
+        total += line.amount;
+    }
+    if !lines.is_empty() && total != ticket.amount {
+        return Err(TicketError::Invalid(
+            "the lines must add up to the ticket amount",
+        ));
+    }
+    sqlx::query!("DELETE FROM tbl_split WHERE movement_id = $1", ticket_id)
+        .execute(&mut *transaction)
+        .await?;
+    let (mut positions, mut type_ids, mut amounts, mut notes) = (vec![], vec![], vec![], vec![]);
+    for (position, line) in lines.iter().enumerate() {
+        positions.push(position as i32);
+        type_ids.push(line.type_id);
+        amounts.push(line.amount);
+        notes.push(line.note.trim().to_string());
+    }
+    sqlx::query!(
+        r#"INSERT INTO tbl_split(movement_id, position, type_id, amount, note)
+        SELECT $1, * FROM UNNEST($2::int[], $3::uuid[], $4::numeric[], $5::text[])"#,
+        ticket_id,
+        &positions,
+        &type_ids,
+        &amounts,
+        &notes,
+    )
+    .execute(&mut *transaction)
+    .await?;
+    transaction.commit().await?;
+    Ok(())
+}
+
+/// The amounts of the lines of a ticket, to check a new ticket amount against.
+pub(crate) async fn line_amounts(
+    pool: &PgPool,
+    user_id: Uuid,
+    ticket_id: Uuid,
+) -> Result<Vec<Decimal>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT ts.amount
+        FROM tbl_split ts
+          INNER JOIN accounting_movement_tbl amt ON amt.id = ts.movement_id
+          INNER JOIN tbl_accounting_member tam ON tam.accounting_id = amt.accounting_id
+        WHERE
+            amt.id = $1 AND tam.user_id = $2"#,
+        ticket_id,
+        user_id,
+    )
+    .fetch_all(pool)
+    .await
+}
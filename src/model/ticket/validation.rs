@@ -0,0 +1,109 @@
+use sqlx::{types::Decimal, PgPool};
+use uuid::Uuid;
+
+/// Check an amount before it reaches `accounting_movement_tbl`.
+///
+/// The sign of a movement is carried by its `TicketDirection`, so the amount itself must be
+/// strictly positive. It can't have more decimals than the `minor_units` of its currency.
+pub fn validate_amount(amount: &Decimal, minor_units: u32) -> Result<(), &'static str> {
+    if amount.is_sign_negative() || amount.is_zero() {
+        return Err("amount must be greater than zero");
+    }
+    if amount.normalize().scale() > minor_units {
+        return Err(match minor_units {
+            0 => "amount can't have decimals in this currency",
+            1 => "amount can have at most 1 decimal place in this currency",
+            2 => "amount can have at most 2 decimal places",
+            _ => "amount can have at most 3 decimal places",
+        });
+    }
+    // amounts are stored as `NUMERIC(15,3)`, which leaves 12 integer digits
+    if *amount >= Decimal::new(1_000_000_000_000, 0) {
+        return Err("amount must be lower than 1000000000000");
+    }
+    Ok(())
+}
+
+/// Decimals allowed in the amount of a ticket: those of `currency` when given, else of the
+/// ticket's own currency on update, or of the book it's written to on creation.
+///
+/// `None` when the currency is unknown, or when the ticket or the book can't be found.
+pub async fn ticket_minor_units(
+    pool: &PgPool,
+    user_id: Uuid,
+    currency: Option<&str>,
+    accounting_id: Option<Uuid>,
+    ticket_id: Option<Uuid>,
+) -> Result<Option<u32>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"SELECT tc.minor_units
+        FROM tbl_currency tc
+        WHERE tc.code = COALESCE(
+            $1,
+            (
+                SELECT amt.currency
+                FROM accounting_movement_tbl amt
+                  INNER JOIN tbl_accounting_member tam ON tam.accounting_id = amt.accounting_id
+                WHERE amt.id = $2 AND tam.user_id = $3
+            ),
+            (
+                SELECT ta.currency
+                FROM tbl_accounting ta
+                  INNER JOIN tbl_accounting_member tam ON tam.accounting_id = ta.id
+                WHERE
+                    tam.user_id = $3
+                    AND ta.id = COALESCE($4, (SELECT current_accounting_id FROM tbl_user WHERE id = $3))
+            )
+        )"#,
+        currency,
+        ticket_id,
+        user_id,
+        accounting_id,
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|row| row.minor_units as u32))
+}
+
+/// Translate the database errors caused by user input into a readable message.
+pub fn user_error(error: &sqlx::Error) -> Option<&'static str> {
+    match error {
+        sqlx::Error::Database(db_error) if db_error.is_foreign_key_violation() => {
+            Some("unknown type_id")
+        }
+        _ => None,
+    }
+}
+
+/// A ticket can be filed under a global category or one of the user's, archived categories are
+/// only accepted for the ticket that already uses them, on itself or one of its split lines.
+/// The same goes for the categories of another member of a shared book.
+pub async fn is_usable_type(
+    pool: &PgPool,
+    user_id: Uuid,
+    type_id: Uuid,
+    ticket_id: Option<Uuid>,
+) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"SELECT EXISTS (
+            SELECT 1 FROM tbl_type tt
+            WHERE
+                tt.id = $1
+                AND (
+                    ((tt.user_id IS NULL OR tt.user_id = $2) AND tt.archived_at IS NULL)
+                    OR EXISTS (
+                        SELECT 1 FROM accounting_movement_tbl WHERE id = $3 AND type_id = tt.id
+                    )
+                    OR EXISTS (
+                        SELECT 1 FROM tbl_split WHERE movement_id = $3 AND type_id = tt.id
+                    )
+                )
+        ) as "usable!""#,
+        type_id,
+        user_id,
+        ticket_id,
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(row.usable)
+}
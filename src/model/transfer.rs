@@ -0,0 +1,192 @@
+//! Money moved between two books of a user, kept as an `out` movement in one book and an `in`
+//! movement in the other sharing a `transfer_id`. Reports leave transfers out by default as
+//! they're neither income nor expense.
+
+use crate::model::{
+    currency::normalize_code,
+    direction::TicketDirection,
+    member::{book_role, forbid_viewer},
+    ticket::{
+        validation::{ticket_minor_units, validate_amount},
+        TicketError,
+    },
+};
+use sqlx::{types::Decimal, PgConnection, PgPool};
+use uuid::Uuid;
+
+/// The global category both sides of a transfer are filed under.
+pub const TRANSFER_TYPE_ID: Uuid = Uuid::from_u128(0x0b6c5f0e_2d4a_4f3b_9c1e_7a8d6e5f4c3b);
+
+#[derive(Debug, serde::Serialize)]
+pub struct Transfer {
+    pub id: Uuid,
+    /// The `out` movement, in the book the money leaves.
+    pub out_ticket_id: Uuid,
+    pub from_accounting_id: Uuid,
+    /// The `in` movement, in the book the money goes to.
+    pub in_ticket_id: Uuid,
+    pub to_accounting_id: Uuid,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
+    pub currency: String,
+    pub description: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct TransferInput {
+    pub from_accounting_id: Uuid,
+    pub to_accounting_id: Uuid,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
+    pub description: String,
+    /// Defaults to now.
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Defaults to the currency of the book the money leaves.
+    pub currency: Option<String>,
+}
+
+/// Create both sides of a transfer at once, returns the id of the transfer.
+pub async fn create(
+    pool: &PgPool,
+    user_id: Uuid,
+    input: &TransferInput,
+) -> Result<Uuid, TicketError> {
+    if input.from_accounting_id == input.to_accounting_id {
+        return Err(TicketError::Invalid("a transfer needs two different books"));
+    }
+    for accounting_id in [input.from_accounting_id, input.to_accounting_id] {
+        forbid_viewer(book_role(pool, user_id, Some(accounting_id)).await?)?;
+    }
+    let currency = input.currency.as_deref().map(normalize_code);
+    let minor_units = match ticket_minor_units(
+        pool,
+        user_id,
+        currency.as_deref(),
+        Some(input.from_accounting_id),
+        None,
+    )
+    .await?
+    {
+        Some(minor_units) => minor_units,
+        None if currency.is_some() => return Err(TicketError::Invalid("unknown currency")),
+        None => return Err(TicketError::Invalid("unknown accounting_id")),
+    };
+    validate_amount(&input.amount, minor_units).map_err(TicketError::Invalid)?;
+    let rows = sqlx::query!(
+        r#"WITH transfer AS (SELECT gen_random_uuid() as id)
+        INSERT INTO accounting_movement_tbl(
+            accounting_id, type_id, direction, amount, description, created_at, currency, transfer_id,
+            created_by
+        )
+        SELECT
+            ta.id,
+            $1,
+            CASE WHEN ta.id = $2 THEN 'out' ELSE 'in' END::MOVEMENT_DIRECTION,
+            ROUND($4, tc.minor_units),
+            $5,
+            $6,
+            tc.code,
+            transfer.id,
+            $8
+        FROM tbl_accounting ta
+          CROSS JOIN transfer
+          INNER JOIN tbl_currency tc
+            ON tc.code = COALESCE($7, (SELECT currency FROM tbl_accounting WHERE id = $2))
+        WHERE
+            ta.id IN ($2, $3)
+            AND (
+                SELECT COUNT(*) FROM tbl_accounting_member
+                WHERE user_id = $8 AND accounting_id IN ($2, $3) AND role <> 'viewer'
+            ) = 2
+        RETURNING transfer_id as "transfer_id!""#,
+        TRANSFER_TYPE_ID,
+        input.from_accounting_id,
+        input.to_accounting_id,
+        input.amount,
+        input.description,
+        input.created_at.unwrap_or(chrono::Utc::now()),
+        currency,
+        user_id,
+    )
+    .fetch_all(pool)
+    .await?;
+    rows.first()
+        .map(|row| row.transfer_id)
+        .ok_or(TicketError::Invalid("unknown accounting_id"))
+}
+
+pub async fn get(pool: &PgPool, user_id: Uuid, transfer_id: Uuid) -> Result<Transfer, TicketError> {
+    sqlx::query_as!(
+        Transfer,
+        r#"SELECT
+            o.transfer_id as "id!",
+            o.id as out_ticket_id,
+            o.accounting_id as from_accounting_id,
+            i.id as in_ticket_id,
+            i.accounting_id as to_accounting_id,
+            o.amount,
+            o.currency,
+            o.description,
+            o.created_at
+        FROM accounting_movement_tbl o
+          INNER JOIN accounting_movement_tbl i ON i.transfer_id = o.transfer_id AND i.direction = 'in'
+        WHERE
+            o.transfer_id = $1 AND o.direction = 'out'
+            AND EXISTS (
+                SELECT 1 FROM tbl_accounting_member
+                WHERE user_id = $2 AND accounting_id IN (o.accounting_id, i.accounting_id)
+            )"#,
+        transfer_id,
+        user_id,
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(TicketError::NotFound)
+}
+
+/// The direction of a ticket and the book of the other side, when the ticket is a side of a
+/// transfer.
+pub(crate) async fn peer(
+    pool: &PgPool,
+    user_id: Uuid,
+    ticket_id: Uuid,
+) -> Result<Option<(TicketDirection, Uuid)>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"SELECT amt.direction as "direction: TicketDirection", peer.accounting_id
+        FROM accounting_movement_tbl amt
+          INNER JOIN tbl_accounting_member tam ON tam.accounting_id = amt.accounting_id
+          INNER JOIN accounting_movement_tbl peer
+            ON peer.transfer_id = amt.transfer_id AND peer.id <> amt.id
+        WHERE
+            amt.id = $1 AND tam.user_id = $2"#,
+        ticket_id,
+        user_id,
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|row| (row.direction, row.accounting_id)))
+}
+
+/// Copy what both sides of a transfer share from a ticket to its other side, if it has one.
+pub(crate) async fn sync_peer(
+    connection: &mut PgConnection,
+    ticket_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"UPDATE accounting_movement_tbl peer
+        SET
+            type_id = amt.type_id,
+            amount = amt.amount,
+            currency = amt.currency,
+            description = amt.description,
+            created_at = amt.created_at
+        FROM accounting_movement_tbl amt
+        WHERE
+            amt.id = $1 AND peer.transfer_id = amt.transfer_id AND peer.id <> amt.id"#,
+        ticket_id,
+    )
+    .execute(connection)
+    .await?;
+    Ok(())
+}
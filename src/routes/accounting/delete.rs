@@ -0,0 +1,48 @@
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult, model::member};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Extension, IntoResponse, Response},
+};
+use uuid::Uuid;
+
+/// Delete an accounting book.
+///
+/// Only owners can delete a book. Books that still hold tickets are kept, the tickets have to be
+/// moved or deleted first.
+pub async fn delete(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(accounting_id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    let Ok(ctx) = ctx_res else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    if let Err(e) = member::require_owner(&state.db_pool, ctx.user_id(), accounting_id).await {
+        return Ok(e.into_response());
+    }
+    let result = sqlx::query!(
+        r#"DELETE FROM tbl_accounting
+        WHERE
+            id = $1
+            AND EXISTS (
+                SELECT 1 FROM tbl_accounting_member
+                WHERE accounting_id = $1 AND user_id = $2 AND role = 'owner'
+            )"#,
+        accounting_id,
+        ctx.user_id(),
+    )
+    .execute(&state.db_pool)
+    .await;
+    match result {
+        Ok(result) if result.rows_affected() == 0 => Err(StatusCode::NOT_FOUND),
+        Ok(_) => Ok(StatusCode::OK.into_response()),
+        Err(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => {
+            Ok((StatusCode::CONFLICT, "accounting still has tickets").into_response())
+        }
+        Err(e) => {
+            tracing::error!("Failed deleting accounting: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult, model::member::BookRole};
+use axum::{extract::State, http::StatusCode, response::Extension, Json};
+use uuid::Uuid;
+
+#[derive(Debug, serde::Serialize)]
+pub struct Accounting {
+    id: Uuid,
+    name: String,
+    description: String,
+    currency: String,
+    created_at: Option<chrono::DateTime<chrono::Utc>>,
+    current: bool,
+    /// What the user can do in the book.
+    role: BookRole,
+}
+
+pub async fn get(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+) -> Result<Json<Vec<Accounting>>, StatusCode> {
+    if ctx_res.is_err() {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    let accountings = sqlx::query_as!(
+        Accounting,
+        r#"SELECT
+            ta.id,
+            ta.name,
+            ta.description,
+            ta.currency,
+            ta.created_at,
+            tu.current_accounting_id IS NOT DISTINCT FROM ta.id as "current!",
+            tam.role as "role: BookRole"
+        FROM tbl_accounting ta
+          INNER JOIN tbl_accounting_member tam ON tam.accounting_id = ta.id
+          INNER JOIN tbl_user tu ON tu.id = tam.user_id
+        WHERE
+            tam.user_id = $1
+        ORDER BY ta.created_at"#,
+        ctx_res.unwrap().user_id()
+    )
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed listing accountings: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(accountings.into())
+}
@@ -0,0 +1,10 @@
+mod delete;
+mod get;
+mod post;
+mod put;
+mod select;
+pub use delete::delete;
+pub use get::get;
+pub use post::post;
+pub use put::put;
+pub use select::select;
@@ -0,0 +1,73 @@
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult, model::currency::normalize_code};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{Extension, IntoResponse, Response},
+    Json,
+};
+use validator::Validate;
+
+#[derive(Debug, Validate, serde::Deserialize)]
+pub struct Accounting {
+    #[validate(length(min = 1, max = 100))]
+    name: String,
+    #[serde(default)]
+    description: String,
+    /// Base currency the reports of the book are converted to.
+    #[serde(default = "default_currency")]
+    currency: String,
+}
+
+fn default_currency() -> String {
+    "EUR".to_string()
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct NewAccounting {
+    id: uuid::Uuid,
+}
+
+pub async fn post(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Json(accounting): Json<Accounting>,
+) -> Result<Response, StatusCode> {
+    if ctx_res.is_err() {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    if accounting.validate().is_err() {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            "name must be between 1 and 100 characters",
+        )
+            .into_response());
+    }
+    let result = sqlx::query_as!(
+        NewAccounting,
+        r#"WITH accounting AS (
+            INSERT INTO tbl_accounting (name, user_id, description, currency) VALUES ($1, $2, $3, $4) RETURNING id
+        )
+        INSERT INTO tbl_accounting_member (accounting_id, user_id, role)
+        SELECT id, $2, 'owner' FROM accounting
+        RETURNING accounting_id as id"#,
+        accounting.name,
+        ctx_res.unwrap().user_id(),
+        accounting.description,
+        normalize_code(&accounting.currency),
+    )
+    .fetch_one(&state.db_pool)
+    .await;
+    match result {
+        Ok(new_accounting) => Ok(Json(new_accounting).into_response()),
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            Ok((StatusCode::CONFLICT, "name already used").into_response())
+        }
+        Err(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => {
+            Ok((StatusCode::BAD_REQUEST, "unknown currency").into_response())
+        }
+        Err(e) => {
+            tracing::error!("Failed creating accounting: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
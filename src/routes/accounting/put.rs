@@ -0,0 +1,72 @@
+use crate::model::{currency::normalize_code, member};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Extension, IntoResponse, Response},
+    Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Validate, serde::Deserialize)]
+pub struct Accounting {
+    #[validate(length(min = 1, max = 100))]
+    name: String,
+    #[serde(default)]
+    description: String,
+    /// Left untouched when missing, the tickets keep their own currency.
+    currency: Option<String>,
+}
+
+pub async fn put(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(accounting_id): Path<Uuid>,
+    Json(accounting): Json<Accounting>,
+) -> Result<Response, StatusCode> {
+    let Ok(ctx) = ctx_res else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    if let Err(e) = member::require_owner(&state.db_pool, ctx.user_id(), accounting_id).await {
+        return Ok(e.into_response());
+    }
+    if accounting.validate().is_err() {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            "name must be between 1 and 100 characters",
+        )
+            .into_response());
+    }
+    let result = sqlx::query!(
+        r#"UPDATE tbl_accounting
+        SET name = $1, description = $2, currency = COALESCE($5, currency)
+        WHERE
+            id = $3
+            AND EXISTS (
+                SELECT 1 FROM tbl_accounting_member
+                WHERE accounting_id = $3 AND user_id = $4 AND role = 'owner'
+            )"#,
+        accounting.name,
+        accounting.description,
+        accounting_id,
+        ctx.user_id(),
+        accounting.currency.as_deref().map(normalize_code),
+    )
+    .execute(&state.db_pool)
+    .await;
+    match result {
+        Ok(result) if result.rows_affected() == 0 => Err(StatusCode::NOT_FOUND),
+        Ok(_) => Ok(StatusCode::OK.into_response()),
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            Ok((StatusCode::CONFLICT, "name already used").into_response())
+        }
+        Err(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => {
+            Ok((StatusCode::BAD_REQUEST, "unknown currency").into_response())
+        }
+        Err(e) => {
+            tracing::error!("Failed updating accounting: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Extension,
+};
+use uuid::Uuid;
+
+/// Make an accounting book the one new tickets go to when no `accounting_id` is given.
+pub async fn select(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(accounting_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    if ctx_res.is_err() {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    let result = sqlx::query!(
+        r#"UPDATE tbl_user tu
+        SET current_accounting_id = tam.accounting_id
+        FROM tbl_accounting_member tam
+        WHERE
+            tam.accounting_id = $1 AND tam.user_id = tu.id AND tu.id = $2"#,
+        accounting_id,
+        ctx_res.unwrap().user_id(),
+    )
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed selecting accounting: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if result.rows_affected() == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,21 @@
+use crate::model::{attachment, ticket::TicketError};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension,
+};
+use uuid::Uuid;
+
+pub async fn delete(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(attachment_id): Path<Uuid>,
+) -> Result<StatusCode, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED);
+    };
+    let storage_key = attachment::delete(&state.db_pool, ctx.user_id(), attachment_id).await?;
+    attachment::remove_blobs(state.blob_store.as_ref(), vec![storage_key]).await;
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,65 @@
+use crate::model::{attachment, ticket::TicketError};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use askama_axum::{IntoResponse, Response};
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    Extension,
+};
+use std::fmt::Write;
+use uuid::Uuid;
+
+/// `Content-Disposition` with an ASCII file name for old clients and the exact one in
+/// `filename*`. Images open in the browser, other files are downloaded.
+fn content_disposition(file_name: &str, content_type: &str) -> String {
+    let disposition = if content_type.starts_with("image/") {
+        "inline"
+    } else {
+        "attachment"
+    };
+    let ascii: String = file_name
+        .chars()
+        .map(|c| if c.is_ascii() { c } else { '_' })
+        .collect();
+    let mut encoded = String::new();
+    for byte in file_name.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            let _ = write!(encoded, "%{byte:02X}");
+        }
+    }
+    format!("{disposition}; filename=\"{ascii}\"; filename*=UTF-8''{encoded}")
+}
+
+/// Download the content of an attachment.
+pub async fn get(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(attachment_id): Path<Uuid>,
+) -> Result<Response, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    let (attachment, storage_key) =
+        attachment::get(&state.db_pool, ctx.user_id(), attachment_id).await?;
+    let data = match state.blob_store.get(&storage_key).await {
+        Ok(data) => data,
+        Err(e) => {
+            tracing::error!("Failed reading attachment {}: {}", storage_key, e);
+            return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+        }
+    };
+    Ok((
+        [
+            (header::CONTENT_TYPE, attachment.content_type.clone()),
+            (
+                header::CONTENT_DISPOSITION,
+                content_disposition(&attachment.file_name, &attachment.content_type),
+            ),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        data,
+    )
+        .into_response())
+}
@@ -0,0 +1,26 @@
+use crate::model::{
+    attachment::{self, Attachment},
+    ticket::TicketError,
+};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use askama_axum::{IntoResponse, Response};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use uuid::Uuid;
+
+/// The attachments of a ticket, oldest first.
+pub async fn list(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(ticket_id): Path<Uuid>,
+) -> Result<Response, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    let attachments: Vec<Attachment> =
+        attachment::list(&state.db_pool, ctx.user_id(), ticket_id).await?;
+    Ok(Json(attachments).into_response())
+}
@@ -0,0 +1,8 @@
+mod delete;
+mod get;
+mod list;
+mod post;
+pub use delete::delete;
+pub use get::get;
+pub use list::list;
+pub use post::post;
@@ -0,0 +1,110 @@
+use crate::model::{
+    attachment,
+    member::{require_write, ticket_role},
+    ticket::TicketError,
+};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use askama_axum::{IntoResponse, Response};
+use axum::{
+    body::Bytes,
+    extract::{Multipart, Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use uuid::Uuid;
+
+#[derive(Debug, serde::Serialize)]
+pub struct NewAttachment {
+    id: Uuid,
+}
+
+/// Read the `file` field of the form, refusing it as soon as it grows over `max_size`.
+async fn read_file(
+    mut multipart: Multipart,
+    max_size: usize,
+) -> Result<(Option<String>, Bytes), Response> {
+    let invalid =
+        |e: axum::extract::multipart::MultipartError| (e.status(), e.body_text()).into_response();
+    while let Some(mut field) = multipart.next_field().await.map_err(invalid)? {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let file_name = field.file_name().map(str::to_string);
+        let mut data = Vec::new();
+        while let Some(chunk) = field.chunk().await.map_err(invalid)? {
+            if data.len() + chunk.len() > max_size {
+                return Err((
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                    format!("attachments are limited to {max_size} bytes"),
+                )
+                    .into_response());
+            }
+            data.extend_from_slice(&chunk);
+        }
+        if data.is_empty() {
+            break;
+        }
+        return Ok((file_name, data.into()));
+    }
+    Err((StatusCode::BAD_REQUEST, "choose a file to upload").into_response())
+}
+
+/// Attach the uploaded `file` to a ticket.
+pub async fn post(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(ticket_id): Path<Uuid>,
+    multipart: Multipart,
+) -> Response {
+    let Ok(ctx) = ctx_res else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    // don't read the upload of a ticket the user can't write
+    let role = match ticket_role(&state.db_pool, ctx.user_id(), ticket_id).await {
+        Ok(role) => role,
+        Err(e) => return TicketError::from(e).into_response(),
+    };
+    if let Err(e) = require_write(role) {
+        return e.into_response();
+    }
+    let (file_name, data) = match read_file(multipart, state.attachments.max_size).await {
+        Ok(file) => file,
+        Err(response) => return response,
+    };
+    let content_type = match attachment::sniff_content_type(&data) {
+        Some(content_type)
+            if state
+                .attachments
+                .mime_types
+                .iter()
+                .any(|t| t == content_type) =>
+        {
+            content_type
+        }
+        _ => return (StatusCode::UNSUPPORTED_MEDIA_TYPE, "unsupported file type").into_response(),
+    };
+    let storage_key = Uuid::new_v4().simple().to_string();
+    let size = data.len() as i64;
+    if let Err(e) = state.blob_store.put(&storage_key, data).await {
+        tracing::error!("Failed storing attachment: {}", e);
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    let created = attachment::create(
+        &state.db_pool,
+        ctx.user_id(),
+        ticket_id,
+        &attachment::clean_file_name(file_name.as_deref()),
+        content_type,
+        size,
+        &storage_key,
+    )
+    .await;
+    match created {
+        Ok(id) => Json(NewAttachment { id }).into_response(),
+        Err(e) => {
+            // the ticket was deleted in the meantime
+            attachment::remove_blobs(state.blob_store.as_ref(), vec![storage_key]).await;
+            e.into_response()
+        }
+    }
+}
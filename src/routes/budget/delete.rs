@@ -0,0 +1,32 @@
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Extension, IntoResponse, Response},
+};
+use uuid::Uuid;
+
+pub async fn delete(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(budget_id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    let Ok(ctx) = ctx_res else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    let result = sqlx::query!(
+        "DELETE FROM tbl_budget WHERE id = $1 AND user_id = $2",
+        budget_id,
+        ctx.user_id(),
+    )
+    .execute(&state.db_pool)
+    .await;
+    match result {
+        Ok(result) if result.rows_affected() == 0 => Err(StatusCode::NOT_FOUND),
+        Ok(_) => Ok(StatusCode::OK.into_response()),
+        Err(e) => {
+            tracing::error!("Failed deleting budget: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult, model::budget};
+use axum::{extract::State, http::StatusCode, response::Extension, Json};
+
+/// List the budgets of the user with their spending in the current period.
+pub async fn get(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+) -> Result<Json<Vec<budget::BudgetStatus>>, StatusCode> {
+    let Ok(ctx) = ctx_res else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    budget::statuses(&state.db_pool, ctx.user_id())
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("Failed fetching budgets: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
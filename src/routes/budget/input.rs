@@ -0,0 +1,72 @@
+use crate::model::{
+    budget::BudgetPeriod,
+    currency,
+    ticket::validation::{is_usable_type, validate_amount},
+};
+use sqlx::{types::Decimal, PgPool};
+use uuid::Uuid;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct BudgetInput {
+    pub type_id: Uuid,
+    /// Restrict the budget to one book, every book of the user is counted otherwise.
+    pub accounting_id: Option<Uuid>,
+    pub period: BudgetPeriod,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: Decimal,
+    /// Currency of the amount, the book's by default or the current book's for every book.
+    pub currency: Option<String>,
+}
+
+impl BudgetInput {
+    /// The reason why the budget can't be saved, if any. The currency is filled in once known.
+    pub async fn invalid(
+        &mut self,
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<Option<&'static str>, sqlx::Error> {
+        if !is_usable_type(pool, user_id, self.type_id, None).await? {
+            return Ok(Some("unknown type_id"));
+        }
+        if let Some(accounting_id) = self.accounting_id {
+            let owned = sqlx::query!(
+                r#"SELECT EXISTS (
+                    SELECT 1 FROM tbl_accounting_member WHERE accounting_id = $1 AND user_id = $2
+                ) as "owned!""#,
+                accounting_id,
+                user_id,
+            )
+            .fetch_one(pool)
+            .await?
+            .owned;
+            if !owned {
+                return Ok(Some("unknown accounting_id"));
+            }
+        }
+        let Some(currency) = sqlx::query!(
+            r#"SELECT tc.code, tc.minor_units
+            FROM tbl_currency tc
+            WHERE tc.code = COALESCE(
+                $1,
+                (
+                    SELECT ta.currency
+                    FROM tbl_accounting ta
+                    WHERE ta.id = COALESCE($2, (SELECT current_accounting_id FROM tbl_user WHERE id = $3))
+                )
+            )"#,
+            self.currency.as_deref().map(currency::normalize_code),
+            self.accounting_id,
+            user_id,
+        )
+        .fetch_optional(pool)
+        .await?
+        else {
+            return Ok(Some("unknown currency"));
+        };
+        if let Err(message) = validate_amount(&self.amount, currency.minor_units as u32) {
+            return Ok(Some(message));
+        }
+        self.currency = Some(currency.code);
+        Ok(None)
+    }
+}
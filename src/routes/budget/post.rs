@@ -0,0 +1,55 @@
+use super::input::BudgetInput;
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult, model::budget::BudgetPeriod};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{Extension, IntoResponse, Response},
+    Json,
+};
+
+#[derive(Debug, serde::Serialize)]
+pub struct NewBudget {
+    id: uuid::Uuid,
+}
+
+pub async fn post(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Json(mut budget): Json<BudgetInput>,
+) -> Result<Response, StatusCode> {
+    let Ok(ctx) = ctx_res else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    match budget.invalid(&state.db_pool, ctx.user_id()).await {
+        Ok(Some(message)) => return Ok((StatusCode::BAD_REQUEST, message).into_response()),
+        Ok(None) => {}
+        Err(e) => {
+            tracing::error!("Failed validating budget: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+    let result = sqlx::query_as!(
+        NewBudget,
+        "INSERT INTO tbl_budget (user_id, type_id, accounting_id, period, amount, currency) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id",
+        ctx.user_id(),
+        budget.type_id,
+        budget.accounting_id,
+        budget.period as BudgetPeriod,
+        budget.amount,
+        budget.currency,
+    )
+    .fetch_one(&state.db_pool)
+    .await;
+    match result {
+        Ok(new_budget) => Ok(Json(new_budget).into_response()),
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => Ok((
+            StatusCode::CONFLICT,
+            "category already has a budget for this period",
+        )
+            .into_response()),
+        Err(e) => {
+            tracing::error!("Failed creating budget: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
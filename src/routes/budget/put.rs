@@ -0,0 +1,53 @@
+use super::input::BudgetInput;
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult, model::budget::BudgetPeriod};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Extension, IntoResponse, Response},
+    Json,
+};
+use uuid::Uuid;
+
+pub async fn put(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(budget_id): Path<Uuid>,
+    Json(mut budget): Json<BudgetInput>,
+) -> Result<Response, StatusCode> {
+    let Ok(ctx) = ctx_res else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    match budget.invalid(&state.db_pool, ctx.user_id()).await {
+        Ok(Some(message)) => return Ok((StatusCode::BAD_REQUEST, message).into_response()),
+        Ok(None) => {}
+        Err(e) => {
+            tracing::error!("Failed validating budget: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+    let result = sqlx::query!(
+        "UPDATE tbl_budget SET type_id = $1, accounting_id = $2, period = $3, amount = $4, currency = $5 WHERE id = $6 AND user_id = $7",
+        budget.type_id,
+        budget.accounting_id,
+        budget.period as BudgetPeriod,
+        budget.amount,
+        budget.currency,
+        budget_id,
+        ctx.user_id(),
+    )
+    .execute(&state.db_pool)
+    .await;
+    match result {
+        Ok(result) if result.rows_affected() == 0 => Err(StatusCode::NOT_FOUND),
+        Ok(_) => Ok(StatusCode::OK.into_response()),
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => Ok((
+            StatusCode::CONFLICT,
+            "category already has a budget for this period",
+        )
+            .into_response()),
+        Err(e) => {
+            tracing::error!("Failed updating budget: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use super::tree::lock_user_tree;
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Extension,
+};
+use std::ops::DerefMut as _;
+use uuid::Uuid;
+
+/// Archive a category owned by the user together with all its children.
+///
+/// Tickets already filed under an archived category keep it and still count in the totals,
+/// the category just can't be picked for new tickets anymore.
+pub async fn delete(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(category_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let Ok(ctx) = ctx_res else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    let internal_error = |e: sqlx::Error| {
+        tracing::error!("Failed archiving category: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+    let mut transaction = state.db_pool.begin().await.map_err(internal_error)?;
+    lock_user_tree(transaction.deref_mut(), ctx.user_id())
+        .await
+        .map_err(internal_error)?;
+    let result = sqlx::query!(
+        r#"WITH RECURSIVE subtree AS (
+            SELECT id FROM tbl_type
+            WHERE id = $1 AND user_id = $2 AND archived_at IS NULL
+            UNION
+            SELECT tt.id
+            FROM tbl_type tt
+              INNER JOIN subtree s ON tt.parent_id = s.id
+        )
+        UPDATE tbl_type
+        SET archived_at = NOW()
+        WHERE
+            id IN (SELECT id FROM subtree) AND user_id = $2 AND archived_at IS NULL"#,
+        category_id,
+        ctx.user_id(),
+    )
+    .execute(transaction.deref_mut())
+    .await
+    .map_err(internal_error)?;
+    if result.rows_affected() == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    transaction.commit().await.map_err(internal_error)?;
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,48 @@
+use super::tree::{build_tree, Category, CategoryRow};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Extension,
+    Json,
+};
+
+#[derive(Debug, serde::Deserialize)]
+pub struct CategoryFilter {
+    #[serde(default)]
+    archived: bool,
+}
+
+/// The global categories merged with the ones of the user, as a tree.
+pub async fn get(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Query(filter): Query<CategoryFilter>,
+) -> Result<Json<Vec<Category>>, StatusCode> {
+    if ctx_res.is_err() {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    let rows = sqlx::query_as!(
+        CategoryRow,
+        r#"SELECT
+            id,
+            parent_id,
+            name,
+            user_id IS NULL as "global!",
+            archived_at IS NOT NULL as "archived!"
+        FROM tbl_type
+        WHERE
+            (user_id IS NULL OR user_id = $1)
+            AND ($2 OR archived_at IS NULL)
+        ORDER BY name"#,
+        ctx_res.unwrap().user_id(),
+        filter.archived,
+    )
+    .fetch_all(&state.db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed listing categories: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(build_tree(rows).into())
+}
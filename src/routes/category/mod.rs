@@ -0,0 +1,9 @@
+mod delete;
+mod get;
+mod post;
+mod put;
+mod tree;
+pub use delete::delete;
+pub use get::get;
+pub use post::post;
+pub use put::put;
@@ -0,0 +1,69 @@
+use super::tree::is_usable_parent;
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{Extension, IntoResponse, Response},
+    Json,
+};
+use std::ops::DerefMut as _;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Validate, serde::Deserialize)]
+pub struct Category {
+    #[validate(length(min = 1, max = 100))]
+    name: String,
+    parent_id: Option<Uuid>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct NewCategory {
+    id: Uuid,
+}
+
+pub async fn post(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Json(category): Json<Category>,
+) -> Result<Response, StatusCode> {
+    let Ok(ctx) = ctx_res else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    if category.validate().is_err() {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            "name must be between 1 and 100 characters",
+        )
+            .into_response());
+    }
+    let mut conn = state.db_pool.acquire().await.map_err(|e| {
+        tracing::error!("Failed acquiring connection: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if let Some(parent_id) = category.parent_id {
+        let usable = is_usable_parent(conn.deref_mut(), ctx.user_id(), parent_id)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed checking category parent: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        if !usable {
+            return Ok((StatusCode::BAD_REQUEST, "unknown parent_id").into_response());
+        }
+    }
+    let new_category = sqlx::query_as!(
+        NewCategory,
+        "INSERT INTO tbl_type (parent_id, name, user_id) VALUES ($1, $2, $3) RETURNING id",
+        category.parent_id,
+        category.name,
+        ctx.user_id(),
+    )
+    .fetch_one(conn.deref_mut())
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed creating category: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(new_category).into_response())
+}
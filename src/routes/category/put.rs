@@ -0,0 +1,80 @@
+use super::tree::{creates_cycle, is_usable_parent, lock_user_tree};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Extension, IntoResponse, Response},
+    Json,
+};
+use std::ops::DerefMut as _;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Validate, serde::Deserialize)]
+pub struct Category {
+    #[validate(length(min = 1, max = 100))]
+    name: String,
+    parent_id: Option<Uuid>,
+}
+
+/// Rename and re-parent a category owned by the user, global categories are read only.
+pub async fn put(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(category_id): Path<Uuid>,
+    Json(category): Json<Category>,
+) -> Result<Response, StatusCode> {
+    let Ok(ctx) = ctx_res else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    if category.validate().is_err() {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            "name must be between 1 and 100 characters",
+        )
+            .into_response());
+    }
+    let internal_error = |e: sqlx::Error| {
+        tracing::error!("Failed updating category: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+    let mut transaction = state.db_pool.begin().await.map_err(internal_error)?;
+    lock_user_tree(transaction.deref_mut(), ctx.user_id())
+        .await
+        .map_err(internal_error)?;
+    if let Some(parent_id) = category.parent_id {
+        if !is_usable_parent(transaction.deref_mut(), ctx.user_id(), parent_id)
+            .await
+            .map_err(internal_error)?
+        {
+            return Ok((StatusCode::BAD_REQUEST, "unknown parent_id").into_response());
+        }
+        if creates_cycle(transaction.deref_mut(), category_id, parent_id)
+            .await
+            .map_err(internal_error)?
+        {
+            return Ok((
+                StatusCode::BAD_REQUEST,
+                "a category can't be moved under itself or one of its children",
+            )
+                .into_response());
+        }
+    }
+    let result = sqlx::query!(
+        r#"UPDATE tbl_type
+        SET name = $1, parent_id = $2
+        WHERE id = $3 AND user_id = $4 AND archived_at IS NULL"#,
+        category.name,
+        category.parent_id,
+        category_id,
+        ctx.user_id(),
+    )
+    .execute(transaction.deref_mut())
+    .await
+    .map_err(internal_error)?;
+    if result.rows_affected() == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    transaction.commit().await.map_err(internal_error)?;
+    Ok(StatusCode::OK.into_response())
+}
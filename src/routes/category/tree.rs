@@ -0,0 +1,103 @@
+use sqlx::PgConnection;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub struct CategoryRow {
+    pub id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub name: String,
+    pub global: bool,
+    pub archived: bool,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct Category {
+    id: Uuid,
+    parent_id: Option<Uuid>,
+    name: String,
+    global: bool,
+    archived: bool,
+    children: Vec<Category>,
+}
+
+/// Nest the flat rows under their parents, rows whose parent is not in the list become roots.
+pub fn build_tree(rows: Vec<CategoryRow>) -> Vec<Category> {
+    let known: HashSet<Uuid> = rows.iter().map(|row| row.id).collect();
+    let mut by_parent: HashMap<Option<Uuid>, Vec<CategoryRow>> = HashMap::new();
+    for row in rows {
+        let parent = row.parent_id.filter(|parent_id| known.contains(parent_id));
+        by_parent.entry(parent).or_default().push(row);
+    }
+    attach_children(None, &mut by_parent)
+}
+
+fn attach_children(
+    parent: Option<Uuid>,
+    by_parent: &mut HashMap<Option<Uuid>, Vec<CategoryRow>>,
+) -> Vec<Category> {
+    by_parent
+        .remove(&parent)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|row| Category {
+            children: attach_children(Some(row.id), by_parent),
+            id: row.id,
+            parent_id: row.parent_id,
+            name: row.name,
+            global: row.global,
+            archived: row.archived,
+        })
+        .collect()
+}
+
+/// Serialize the tree edits of a user, so two concurrent re-parents can't build a cycle.
+pub async fn lock_user_tree(conn: &mut PgConnection, user_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query!("SELECT id FROM tbl_user WHERE id = $1 FOR UPDATE", user_id)
+        .fetch_one(conn)
+        .await?;
+    Ok(())
+}
+
+/// A category can be used as parent when it's global or owned by the user and not archived.
+pub async fn is_usable_parent(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    parent_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"SELECT EXISTS (
+            SELECT 1 FROM tbl_type
+            WHERE id = $1 AND (user_id IS NULL OR user_id = $2) AND archived_at IS NULL
+        ) as "usable!""#,
+        parent_id,
+        user_id,
+    )
+    .fetch_one(conn)
+    .await?;
+    Ok(row.usable)
+}
+
+/// Moving `category_id` under `parent_id` makes a cycle when the category is one of the
+/// ancestors of the new parent (or the parent itself).
+pub async fn creates_cycle(
+    conn: &mut PgConnection,
+    category_id: Uuid,
+    parent_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"WITH RECURSIVE ancestors AS (
+            SELECT id, parent_id FROM tbl_type WHERE id = $1
+            UNION
+            SELECT tt.id, tt.parent_id
+            FROM tbl_type tt
+              INNER JOIN ancestors a ON tt.id = a.parent_id
+        )
+        SELECT EXISTS (SELECT 1 FROM ancestors WHERE id = $2) as "cycle!""#,
+        parent_id,
+        category_id,
+    )
+    .fetch_one(conn)
+    .await?;
+    Ok(row.cycle)
+}
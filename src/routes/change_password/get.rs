@@ -0,0 +1,11 @@
+use crate::auth::mw_auth::CtxResult;
+use crate::templates::ChangePasswordPage;
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::Extension;
+
+pub async fn get(Extension(ctx_res): Extension<CtxResult>) -> Response {
+    match ctx_res {
+        Ok(_) => ChangePasswordPage {}.into_response(),
+        Err(_) => Redirect::to("/login").into_response(),
+    }
+}
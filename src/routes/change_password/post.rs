@@ -0,0 +1,106 @@
+use crate::app_state::SharedAppState;
+use crate::auth::{
+    error::AuthError,
+    logout::logout_sessions,
+    mw_auth::CtxResult,
+    password::{hash_password, is_password_strong, validate_credentials, Credentials},
+    throttle::{self, Account},
+};
+use crate::templates::validation::password::PasswordValidation;
+use askama_axum::{IntoResponse, Response};
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, StatusCode},
+    Extension, Form,
+};
+use secrecy::{ExposeSecret, SecretString};
+use std::net::SocketAddr;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct PasswordChange {
+    #[serde(rename = "current-password")]
+    current_password: SecretString,
+    password: SecretString,
+    #[serde(rename = "password-confirm")]
+    password_confirm: SecretString,
+}
+
+/// Replace the password of the user, their sessions on other devices are ended.
+#[tracing::instrument(name = "change password", skip_all)]
+pub async fn post(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Form(change): Form<PasswordChange>,
+) -> Result<Response, StatusCode> {
+    let Ok(ctx) = ctx_res else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    if change.password.expose_secret() != change.password_confirm.expose_secret() {
+        return Ok((StatusCode::BAD_REQUEST, "password confirm error").into_response());
+    }
+    if !is_password_strong(&change.password) {
+        return Ok((PasswordValidation {}).into_response());
+    }
+    // guessing the current password is throttled as on the login page
+    let mut conn = state
+        .redis_pool
+        .get()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let throttle = &state.login_throttle;
+    let account = Account::User(ctx.user_id());
+    if let Err(e) = throttle::check(&mut conn, throttle, &account, addr.ip()).await {
+        return Ok(e.into_response());
+    }
+    let username =
+        sqlx::query_scalar!("SELECT username FROM tbl_user WHERE id = $1", ctx.user_id())
+            .fetch_one(&state.db_pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed fetching user: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+    let credentials = Credentials {
+        email_or_user: username,
+        password: change.current_password,
+    };
+    match validate_credentials(credentials, &state.db_pool).await {
+        Ok(user_id) if user_id == ctx.user_id() => {}
+        Ok(_) | Err(AuthError::InvalidCredentials(_)) => {
+            return match throttle::record_failure(&mut conn, throttle, &account, addr.ip()).await {
+                Ok(()) => Ok((StatusCode::BAD_REQUEST, "wrong current password").into_response()),
+                Err(e) => Ok(e.into_response()),
+            };
+        }
+        Err(AuthError::UnexpectedError(e)) => {
+            tracing::error!("Failed validating credentials: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+    if let Err(e) = throttle::record_success(&mut conn, throttle, &account).await {
+        return Ok(e.into_response());
+    }
+    let hashed_password = hash_password(change.password)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    sqlx::query!(
+        "UPDATE tbl_user SET password = $1 WHERE id = $2",
+        hashed_password.expose_secret(),
+        ctx.user_id(),
+    )
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed changing password: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if let Err(e) = logout_sessions(ctx.user_id(), Some(&ctx.session_id()), conn).await {
+        tracing::error!("Failed ending the other sessions: {}", e);
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
+    let mut headers = HeaderMap::new();
+    headers.append("HX-Redirect", "/home".parse().unwrap());
+    Ok((headers, StatusCode::OK).into_response())
+}
@@ -0,0 +1,20 @@
+use crate::{
+    app_state::SharedAppState,
+    auth::mw_auth::CtxResult,
+    model::currency::{self, Currency},
+};
+use axum::{extract::State, http::StatusCode, response::Extension, Json};
+
+/// The currencies books and tickets can be kept in.
+pub async fn get(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+) -> Result<Json<Vec<Currency>>, StatusCode> {
+    if ctx_res.is_err() {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    currency::list(&state.db_pool).await.map(Json).map_err(|e| {
+        tracing::error!("Failed listing currencies: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
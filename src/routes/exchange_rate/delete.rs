@@ -0,0 +1,25 @@
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult, model::currency};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Extension,
+};
+use chrono::NaiveDate;
+
+pub async fn delete(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path((base, quote, day)): Path<(String, String, NaiveDate)>,
+) -> Result<StatusCode, StatusCode> {
+    let Ok(ctx) = ctx_res else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    match currency::delete_rate(&state.db_pool, ctx.user_id(), &base, &quote, day).await {
+        Ok(true) => Ok(StatusCode::OK),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed deleting exchange rate: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use crate::{
+    app_state::SharedAppState,
+    auth::mw_auth::CtxResult,
+    model::currency::{self, ExchangeRate},
+};
+use axum::{extract::State, http::StatusCode, response::Extension, Json};
+
+/// List the exchange rates of the user, latest first for each pair.
+pub async fn get(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+) -> Result<Json<Vec<ExchangeRate>>, StatusCode> {
+    let Ok(ctx) = ctx_res else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    currency::rates(&state.db_pool, ctx.user_id())
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("Failed listing exchange rates: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
@@ -0,0 +1,28 @@
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult, model::currency};
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::StatusCode,
+    response::{Extension, IntoResponse, Response},
+    Json,
+};
+
+#[derive(Debug, serde::Serialize)]
+pub struct ImportedRates {
+    imported: usize,
+}
+
+/// Import the CSV sent as the request body, one `date,base,quote,rate` line per rate.
+pub async fn import(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    body: Bytes,
+) -> Response {
+    let Ok(ctx) = ctx_res else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    match currency::import_rates(&state.db_pool, ctx.user_id(), &body).await {
+        Ok(imported) => Json(ImportedRates { imported }).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
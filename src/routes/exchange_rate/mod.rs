@@ -0,0 +1,8 @@
+mod delete;
+mod get;
+mod import;
+mod put;
+pub use delete::delete;
+pub use get::get;
+pub use import::import;
+pub use put::put;
@@ -0,0 +1,26 @@
+use crate::{
+    app_state::SharedAppState,
+    auth::mw_auth::CtxResult,
+    model::currency::{self, ExchangeRate},
+};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{Extension, IntoResponse, Response},
+    Json,
+};
+
+/// Set the rate of a pair for a day, replacing the one already there.
+pub async fn put(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Json(rate): Json<ExchangeRate>,
+) -> Response {
+    let Ok(ctx) = ctx_res else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    match currency::save_rate(&state.db_pool, ctx.user_id(), rate).await {
+        Ok(()) => StatusCode::OK.into_response(),
+        Err(e) => e.into_response(),
+    }
+}
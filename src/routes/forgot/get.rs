@@ -0,0 +1,6 @@
+use crate::templates::ForgotPage;
+use axum::response::{IntoResponse, Response};
+
+pub async fn get() -> Response {
+    ForgotPage {}.into_response()
+}
@@ -0,0 +1,56 @@
+use crate::app_state::{AppState, SharedAppState};
+use crate::auth::password_reset::{self, TOKEN_TTL_MINUTES};
+use crate::mail::Mail;
+use anyhow::Context;
+use axum::{extract::State, Form};
+use tracing::Instrument;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ForgotInput {
+    email_or_user: String,
+}
+
+const SENT: &str = "If an account matches, a reset link has been sent to its email.";
+
+/// Mail a password reset link. The answer is the same whether the account exists or not, and
+/// is given before looking the account up so it takes as long either way.
+#[tracing::instrument(name = "forgot password", skip_all)]
+pub async fn post(
+    State(state): State<SharedAppState>,
+    Form(input): Form<ForgotInput>,
+) -> &'static str {
+    tokio::spawn(
+        async move {
+            if let Err(e) = send_link(&state, &input.email_or_user).await {
+                tracing::error!("Failed sending password reset: {:?}", e);
+            }
+        }
+        .in_current_span(),
+    );
+    SENT
+}
+
+async fn send_link(state: &AppState, email_or_user: &str) -> anyhow::Result<()> {
+    let mut conn = state
+        .redis_pool
+        .get()
+        .await
+        .context("Failed getting redis connection")?;
+    let Some((email, token)) =
+        password_reset::create_token(&state.db_pool, &mut conn, email_or_user).await?
+    else {
+        return Ok(());
+    };
+    let mail = Mail {
+        to: email,
+        subject: "Reset your TicketApp password".to_string(),
+        body: format!(
+            "Someone asked to reset the password of your TicketApp account.\n\n\
+            Choose a new one within {TOKEN_TTL_MINUTES} minutes at:\n{}/reset_password?token={token}\n\n\
+            Nothing changes if you ignore this mail.",
+            state.base_url.trim_end_matches('/'),
+        ),
+    };
+    state.mailer.send(mail).await?;
+    Ok(())
+}
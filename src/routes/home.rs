@@ -1,14 +1,39 @@
 use askama_axum::{IntoResponse, Response};
-use axum::{response::Redirect, Extension};
+use axum::{extract::State, response::Redirect, Extension};
 
-use crate::{auth::mw_auth::CtxResult, templates::HomePage};
+use crate::{
+    app_state::SharedAppState,
+    auth::{email_verification, mw_auth::CtxResult},
+    model::budget,
+    templates::HomePage,
+};
 
-pub async fn get(Extension(ctx_res): Extension<CtxResult>) -> Response {
+pub async fn get(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+) -> Response {
     match ctx_res {
-        Ok(ctx) => HomePage {
-            user: ctx.user_id().to_string().into(),
+        Ok(ctx) => {
+            // the page is still usable without the budget widget
+            let budgets = budget::statuses(&state.db_pool, ctx.user_id())
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::error!("Failed fetching budgets: {}", e);
+                    Vec::new()
+                });
+            let email_verified = email_verification::is_verified(&state.db_pool, ctx.user_id())
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::error!("Failed checking email verification: {}", e);
+                    true
+                });
+            HomePage {
+                user: ctx.user_id().to_string().into(),
+                budgets: budgets.into_iter().map(Into::into).collect(),
+                email_verified,
+            }
+            .into_response()
         }
-        .into_response(),
         Err(_) => Redirect::to("/login").into_response(),
     }
 }
@@ -0,0 +1,22 @@
+use crate::model::{import, ticket::TicketError};
+use crate::templates::import::ImportDone;
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use askama_axum::{IntoResponse, Response};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension,
+};
+use uuid::Uuid;
+
+pub async fn commit(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(import_id): Path<Uuid>,
+) -> Result<Response, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    let created = import::commit(&state.db_pool, ctx.user_id(), import_id).await?;
+    Ok(ImportDone { created }.into_response())
+}
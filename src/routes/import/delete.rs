@@ -0,0 +1,20 @@
+use crate::model::{import, ticket::TicketError};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Extension,
+};
+use uuid::Uuid;
+
+pub async fn delete(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(import_id): Path<Uuid>,
+) -> Result<StatusCode, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED);
+    };
+    import::discard(&state.db_pool, ctx.user_id(), import_id).await?;
+    Ok(StatusCode::OK)
+}
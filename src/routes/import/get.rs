@@ -0,0 +1,36 @@
+use crate::model::{category, ticket::TicketError};
+use crate::templates::import::{AccountingOption, ImportPage};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use askama_axum::{IntoResponse, Response};
+use axum::{extract::State, http::StatusCode, Extension};
+
+pub async fn get(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+) -> Result<Response, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    let accountings = sqlx::query_as!(
+        AccountingOption,
+        r#"SELECT
+            ta.id,
+            ta.name,
+            tu.current_accounting_id IS NOT DISTINCT FROM ta.id as "current!"
+        FROM tbl_accounting ta
+          INNER JOIN tbl_accounting_member tam ON tam.accounting_id = ta.id
+          INNER JOIN tbl_user tu ON tu.id = tam.user_id
+        WHERE
+            tam.user_id = $1 AND tam.role <> 'viewer'
+        ORDER BY ta.created_at"#,
+        ctx.user_id()
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+    let categories = category::usable_paths(&state.db_pool, ctx.user_id()).await?;
+    Ok(ImportPage {
+        accountings,
+        categories,
+    }
+    .into_response())
+}
@@ -0,0 +1,8 @@
+mod commit;
+mod delete;
+mod get;
+mod post;
+pub use commit::commit;
+pub use delete::delete;
+pub use get::get;
+pub use post::post;
@@ -0,0 +1,105 @@
+use crate::model::{
+    import::{self, csv::CsvMapping, StatementFormat},
+    ticket::TicketError,
+};
+use crate::routes::tickets::form::error_response;
+use crate::templates::import::ImportPreview;
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use askama_axum::{IntoResponse, Response};
+use axum::{
+    extract::{Multipart, State},
+    http::StatusCode,
+    Extension,
+};
+use uuid::Uuid;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ImportForm {
+    accounting_id: Uuid,
+    /// Category of the rows without one.
+    type_id: Uuid,
+    #[serde(default)]
+    format: StatementFormat,
+}
+
+/// Read the uploaded file and the text fields of the form, empty fields are left out.
+async fn read_form(mut multipart: Multipart) -> Result<(serde_json::Value, Vec<u8>), TicketError> {
+    let mut fields = serde_json::Map::new();
+    let mut file = Vec::new();
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|_| TicketError::Invalid("invalid upload"))?
+    {
+        let name = field.name().unwrap_or_default().to_string();
+        if name == "file" {
+            file = field
+                .bytes()
+                .await
+                .map_err(|_| TicketError::Invalid("invalid upload"))?
+                .to_vec();
+        } else {
+            let value = field
+                .text()
+                .await
+                .map_err(|_| TicketError::Invalid("invalid upload"))?;
+            if !value.is_empty() {
+                fields.insert(name, value.into());
+            }
+        }
+    }
+    if file.is_empty() {
+        return Err(TicketError::Invalid("choose a file to import"));
+    }
+    Ok((fields.into(), file))
+}
+
+/// Read the rows of the statement, the column mapping is only needed for CSV files.
+fn parse(
+    format: StatementFormat,
+    fields: serde_json::Value,
+    file: &[u8],
+) -> Result<Vec<import::ParsedRow>, TicketError> {
+    let rows = match format {
+        StatementFormat::Csv => {
+            let mapping: CsvMapping = serde_json::from_value(fields)
+                .map_err(|_| TicketError::Invalid("invalid column mapping"))?;
+            import::csv::parse(file, &mapping)
+        }
+        StatementFormat::Ofx => import::ofx::parse(file),
+        StatementFormat::Qif => import::qif::parse(file),
+        StatementFormat::Camt => import::camt::parse(file),
+    };
+    rows.map_err(TicketError::Invalid)
+}
+
+/// Stage an uploaded statement and answer with its preview.
+pub async fn post(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    multipart: Multipart,
+) -> Response {
+    let Ok(ctx) = ctx_res else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let preview = async {
+        let (fields, file) = read_form(multipart).await?;
+        let form: ImportForm = serde_json::from_value(fields.clone())
+            .map_err(|_| TicketError::Invalid("choose a book and a default category"))?;
+        let rows = parse(form.format, fields, &file)?;
+        let import_id = import::stage(
+            &state.db_pool,
+            ctx.user_id(),
+            form.accounting_id,
+            form.type_id,
+            rows,
+        )
+        .await?;
+        let rows = import::preview(&state.db_pool, ctx.user_id(), import_id).await?;
+        Ok(ImportPreview::new(import_id, rows))
+    };
+    match preview.await {
+        Ok(preview) => preview.into_response(),
+        Err(e) => error_response(e, "import-error"),
+    }
+}
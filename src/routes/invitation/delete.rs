@@ -0,0 +1,21 @@
+use crate::model::member::{self, MemberError};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Extension,
+};
+use uuid::Uuid;
+
+/// Decline an invitation, or withdraw it as an owner of the book.
+pub async fn delete(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(invitation_id): Path<Uuid>,
+) -> Result<StatusCode, MemberError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED);
+    };
+    member::delete_invitation(&state.db_pool, ctx.user_id(), invitation_id).await?;
+    Ok(StatusCode::OK)
+}
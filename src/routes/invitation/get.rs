@@ -0,0 +1,20 @@
+use crate::model::member::{self, Invitation};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{extract::State, http::StatusCode, response::Extension, Json};
+
+/// The invitations waiting for the user.
+pub async fn get(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+) -> Result<Json<Vec<Invitation>>, StatusCode> {
+    let Ok(ctx) = ctx_res else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    let invitations = member::invitations(&state.db_pool, ctx.user_id())
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed listing invitations: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(invitations.into())
+}
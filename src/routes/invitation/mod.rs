@@ -0,0 +1,6 @@
+mod accept;
+mod delete;
+mod get;
+pub use accept::accept;
+pub use delete::delete;
+pub use get::get;
@@ -1,4 +1,8 @@
 mod get;
 mod post;
+mod totp;
+mod totp_page;
 pub use get::get;
 pub use post::post;
+pub use totp::totp;
+pub use totp_page::totp_page;
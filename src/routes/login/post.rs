@@ -1,49 +1,108 @@
-use tower_cookies::cookie::time::Duration;
-
 use crate::{
     app_state::SharedAppState,
     auth::{
-        mw_auth::{CtxResult, AUTH_COOKIE},
+        email_verification::{self, VerificationError},
+        error::AuthError,
+        mw_auth::CtxResult,
         password::{validate_credentials, Credentials},
-        session_key::generate_session_key,
+        session::{start_pending_login, start_session, Client},
+        throttle::{self, Account},
+        totp,
     },
+    configuration::UnverifiedAccess,
 };
 use axum::{
-    extract::{Extension, State},
+    extract::{ConnectInfo, Extension, State},
     http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
     Form,
 };
-use bb8_redis::redis::{AsyncCommands, ExistenceCheck, SetExpiry, SetOptions};
-use tower_cookies::{Cookie, Cookies};
+use std::net::SocketAddr;
+use tower_cookies::Cookies;
 
 pub async fn post(
     State(state): State<SharedAppState>,
     ctx_res: Extension<CtxResult>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request_headers: HeaderMap,
     cookies: Cookies,
     Form(credentials): Form<Credentials>,
 ) -> Response {
     if ctx_res.is_ok() {
         return StatusCode::OK.into_response();
     }
+    let mut conn = match state.redis_pool.get().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            tracing::error!("Failed acquiring redis connection: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let throttle = &state.login_throttle;
+    let account = match Account::resolve(&state.db_pool, &credentials.email_or_user).await {
+        Ok(account) => account,
+        Err(e) => return e.into_response(),
+    };
+    if let Err(e) = throttle::check(&mut conn, throttle, &account, addr.ip()).await {
+        return e.into_response();
+    }
     match validate_credentials(credentials, &state.db_pool).await {
         Ok(user_id) => {
-            tracing::Span::current().record("user_id", &tracing::field::display(&user_id));
-            let mut conn = state.redis_pool.get().await.unwrap();
-            let opts = SetOptions::default()
-                .conditional_set(ExistenceCheck::NX)
-                .with_expiration(SetExpiry::EX(60));
-            // add session in redis
-            let session_key = generate_session_key();
-            let _: () = conn.set_options(&session_key, user_id, opts).await.unwrap();
-            let mut auth_cookie = Cookie::new(AUTH_COOKIE, session_key.as_ref().to_string());
-            auth_cookie.set_max_age(Duration::seconds(10));
-            auth_cookie.set_http_only(true);
-            cookies.add(auth_cookie);
+            tracing::Span::current().record("user_id", tracing::field::display(&user_id));
+            if state.email_verification.unverified_access == UnverifiedAccess::None {
+                match email_verification::is_verified(&state.db_pool, user_id).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        match email_verification::send_link(&state, user_id).await {
+                            Ok(()) | Err(VerificationError::TooSoon(_)) => {}
+                            Err(e) => tracing::error!("Failed sending confirmation mail: {}", e),
+                        }
+                        return (
+                            StatusCode::FORBIDDEN,
+                            "confirm your email address first, a link has been sent to it",
+                        )
+                            .into_response();
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed checking email verification: {}", e);
+                        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+                    }
+                }
+            }
+            let with_totp = match totp::is_enabled(&state.db_pool, user_id).await {
+                Ok(with_totp) => with_totp,
+                Err(e) => {
+                    tracing::error!("Failed checking two-factor authentication: {}", e);
+                    return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+                }
+            };
             let mut headers = HeaderMap::new();
-            headers.append("HX-Redirect", "/home".parse().unwrap());
+            // with a second factor the session waits for the code, failures are kept until then
+            if with_totp {
+                if let Err(e) = start_pending_login(&mut conn, &cookies, user_id).await {
+                    tracing::error!("Failed starting pending login: {}", e);
+                    return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+                }
+                headers.append("HX-Redirect", "/login/totp".parse().unwrap());
+            } else {
+                if let Err(e) = throttle::record_success(&mut conn, throttle, &account).await {
+                    return e.into_response();
+                }
+                let client = Client::new(addr, &request_headers);
+                if let Err(e) = start_session(&mut conn, &cookies, user_id, client).await {
+                    tracing::error!("Failed starting session: {}", e);
+                    return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+                }
+                headers.append("HX-Redirect", "/home".parse().unwrap());
+            }
             (headers, StatusCode::OK).into_response()
         }
+        Err(AuthError::InvalidCredentials(_)) => {
+            match throttle::record_failure(&mut conn, throttle, &account, addr.ip()).await {
+                Ok(()) => StatusCode::BAD_REQUEST.into_response(),
+                Err(e) => e.into_response(),
+            }
+        }
         Err(_) => StatusCode::BAD_REQUEST.into_response(),
     }
 }
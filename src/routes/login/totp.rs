@@ -0,0 +1,91 @@
+use crate::{
+    app_state::SharedAppState,
+    auth::{
+        mw_auth::CtxResult,
+        session::{end_pending_login, pending_login_attempt, start_session, Client},
+        throttle::{self, Account},
+        totp,
+    },
+};
+use axum::{
+    extract::{ConnectInfo, Extension, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Form,
+};
+use std::net::SocketAddr;
+use tower_cookies::Cookies;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct TotpInput {
+    /// A code of the authenticator app or a recovery code.
+    code: String,
+}
+
+/// Second step of the login for users with two-factor authentication.
+pub async fn totp(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request_headers: HeaderMap,
+    cookies: Cookies,
+    Form(input): Form<TotpInput>,
+) -> Result<Response, StatusCode> {
+    if ctx_res.is_ok() {
+        return Ok(StatusCode::OK.into_response());
+    }
+    let mut conn = state
+        .redis_pool
+        .get()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let user_id = pending_login_attempt(&mut conn, &cookies)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed reading pending login: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let Some(user_id) = user_id else {
+        let mut headers = HeaderMap::new();
+        headers.append("HX-Redirect", "/login".parse().unwrap());
+        return Ok((headers, StatusCode::UNAUTHORIZED).into_response());
+    };
+    tracing::Span::current().record("user_id", tracing::field::display(&user_id));
+    let throttle = &state.login_throttle;
+    let account = Account::User(user_id);
+    if let Err(e) = throttle::check(&mut conn, throttle, &account, addr.ip()).await {
+        return Ok(e.into_response());
+    }
+    let valid = totp::verify(&state.db_pool, user_id, &input.code)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed checking two-factor code: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if !valid {
+        return match throttle::record_failure(&mut conn, throttle, &account, addr.ip()).await {
+            Ok(()) => Ok((StatusCode::BAD_REQUEST, "wrong code").into_response()),
+            Err(e) => Ok(e.into_response()),
+        };
+    }
+    if let Err(e) = throttle::record_success(&mut conn, throttle, &account).await {
+        return Ok(e.into_response());
+    }
+    let started = async {
+        end_pending_login(&mut conn, &cookies).await?;
+        start_session(
+            &mut conn,
+            &cookies,
+            user_id,
+            Client::new(addr, &request_headers),
+        )
+        .await
+    };
+    started.await.map_err(|e| {
+        tracing::error!("Failed starting session: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let mut headers = HeaderMap::new();
+    headers.append("HX-Redirect", "/home".parse().unwrap());
+    Ok((headers, StatusCode::OK).into_response())
+}
@@ -0,0 +1,19 @@
+use crate::{
+    auth::{mw_auth::CtxResult, session::PENDING_LOGIN_COOKIE},
+    templates::LoginTotpPage,
+};
+use axum::{
+    extract::Extension,
+    response::{IntoResponse, Redirect, Response},
+};
+use tower_cookies::Cookies;
+
+pub async fn totp_page(Extension(ctx_res): Extension<CtxResult>, cookies: Cookies) -> Response {
+    if ctx_res.is_ok() {
+        return Redirect::to("/home").into_response();
+    }
+    if cookies.get(PENDING_LOGIN_COOKIE).is_none() {
+        return Redirect::to("/login").into_response();
+    }
+    LoginTotpPage {}.into_response()
+}
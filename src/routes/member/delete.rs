@@ -0,0 +1,21 @@
+use crate::model::member::{self, MemberError};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Extension,
+};
+use uuid::Uuid;
+
+/// Remove a member from a book, or leave it when the member is the user.
+pub async fn delete(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path((accounting_id, member_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, MemberError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED);
+    };
+    member::remove(&state.db_pool, ctx.user_id(), accounting_id, member_id).await?;
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,23 @@
+use crate::model::member::{self, Member, MemberError};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Extension, IntoResponse, Response},
+    Json,
+};
+use uuid::Uuid;
+
+/// The members of a book with their role.
+pub async fn get(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(accounting_id): Path<Uuid>,
+) -> Result<Response, MemberError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    let members: Vec<Member> =
+        member::members(&state.db_pool, ctx.user_id(), accounting_id).await?;
+    Ok(Json(members).into_response())
+}
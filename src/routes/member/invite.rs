@@ -0,0 +1,37 @@
+use crate::model::member::{self, BookRole, MemberError};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Extension, IntoResponse, Response},
+    Json,
+};
+use uuid::Uuid;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct InvitationInput {
+    /// Username or email of the invited user.
+    user: String,
+    role: BookRole,
+}
+
+/// Invite a user to a book, only owners can.
+pub async fn invite(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(accounting_id): Path<Uuid>,
+    Json(input): Json<InvitationInput>,
+) -> Result<Response, MemberError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    let id = member::invite(
+        &state.db_pool,
+        ctx.user_id(),
+        accounting_id,
+        &input.user,
+        input.role,
+    )
+    .await?;
+    Ok(Json(serde_json::json!({ "id": id })).into_response())
+}
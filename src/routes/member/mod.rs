@@ -0,0 +1,8 @@
+mod delete;
+mod get;
+mod invite;
+mod put;
+pub use delete::delete;
+pub use get::get;
+pub use invite::invite;
+pub use put::put;
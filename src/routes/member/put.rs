@@ -0,0 +1,35 @@
+use crate::model::member::{self, BookRole, MemberError};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Extension,
+    Json,
+};
+use uuid::Uuid;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct RoleInput {
+    role: BookRole,
+}
+
+/// Change the role of a member, only owners can.
+pub async fn put(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path((accounting_id, member_id)): Path<(Uuid, Uuid)>,
+    Json(input): Json<RoleInput>,
+) -> Result<StatusCode, MemberError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED);
+    };
+    member::set_role(
+        &state.db_pool,
+        ctx.user_id(),
+        accounting_id,
+        member_id,
+        input.role,
+    )
+    .await?;
+    Ok(StatusCode::OK)
+}
@@ -1,10 +1,31 @@
+pub mod accounting;
+pub mod attachment;
+pub mod budget;
+pub mod category;
+pub mod change_password;
+pub mod currency;
+pub mod exchange_rate;
+pub mod forgot;
 mod health_check;
 pub mod home;
+pub mod import;
 mod index;
+pub mod invitation;
 pub mod login;
 pub mod logout;
+pub mod member;
+pub mod recurring;
+pub mod report;
+pub mod reset_password;
+pub mod session;
 pub mod signup;
+pub mod split;
+pub mod tag;
 pub mod ticket;
+pub mod tickets;
+pub mod totp;
+pub mod transfer;
 pub mod validate;
+pub mod verify_email;
 pub use health_check::health_check;
 pub use index::index;
@@ -0,0 +1,22 @@
+use crate::model::{
+    recurring::{self, Recurring},
+    ticket::TicketError,
+};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{Extension, IntoResponse, Response},
+    Json,
+};
+
+pub async fn get(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+) -> Result<Response, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    let rules: Vec<Recurring> = recurring::list(&state.db_pool, ctx.user_id()).await?;
+    Ok(Json(rules).into_response())
+}
@@ -0,0 +1,8 @@
+mod delete;
+mod get;
+mod post;
+mod put;
+pub use delete::delete;
+pub use get::get;
+pub use post::post;
+pub use put::put;
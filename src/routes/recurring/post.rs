@@ -0,0 +1,28 @@
+use crate::model::{
+    recurring::{self, RecurringInput},
+    ticket::TicketError,
+};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{Extension, IntoResponse, Response},
+    Json,
+};
+
+#[derive(Debug, serde::Serialize)]
+pub struct NewRecurring {
+    id: uuid::Uuid,
+}
+
+pub async fn post(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Json(rule): Json<RecurringInput>,
+) -> Result<Response, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    let id = recurring::create(&state.db_pool, ctx.user_id(), &rule).await?;
+    Ok(Json(NewRecurring { id }).into_response())
+}
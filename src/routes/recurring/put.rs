@@ -0,0 +1,25 @@
+use crate::model::{
+    recurring::{self, RecurringInput},
+    ticket::TicketError,
+};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Extension,
+    Json,
+};
+use uuid::Uuid;
+
+pub async fn put(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(recurring_id): Path<Uuid>,
+    Json(rule): Json<RecurringInput>,
+) -> Result<StatusCode, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED);
+    };
+    recurring::update(&state.db_pool, ctx.user_id(), recurring_id, &rule).await?;
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,14 @@
+mod categories;
+mod periods;
+mod tags;
+pub use categories::categories;
+pub use periods::periods;
+pub use tags::tags;
+
+use crate::model::report::Period;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ReportQuery {
+    #[serde(default)]
+    period: Period,
+}
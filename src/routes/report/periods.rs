@@ -0,0 +1,31 @@
+use super::ReportQuery;
+use crate::model::{
+    report::{self, PeriodTotal},
+    ticket::filter::TicketFilter,
+};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Extension,
+    Json,
+};
+
+/// Money in, money out and net of every period.
+pub async fn periods(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Query(query): Query<ReportQuery>,
+    Query(filter): Query<TicketFilter>,
+) -> Result<Json<Vec<PeriodTotal>>, StatusCode> {
+    let Ok(ctx) = ctx_res else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    let totals = report::period_totals(&state.db_pool, ctx.user_id(), query.period, &filter)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed computing period report: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(totals.into())
+}
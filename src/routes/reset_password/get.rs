@@ -0,0 +1,29 @@
+use crate::{app_state::SharedAppState, auth::password_reset, templates::ResetPasswordPage};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ResetQuery {
+    #[serde(default)]
+    token: String,
+}
+
+pub async fn get(
+    State(state): State<SharedAppState>,
+    Query(query): Query<ResetQuery>,
+) -> Result<Response, StatusCode> {
+    let valid = password_reset::is_token_valid(&state.db_pool, &query.token)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed checking password reset: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(ResetPasswordPage {
+        token: query.token,
+        valid,
+    }
+    .into_response())
+}
@@ -0,0 +1,4 @@
+mod get;
+mod post;
+pub use get::get;
+pub use post::post;
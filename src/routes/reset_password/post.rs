@@ -0,0 +1,80 @@
+use crate::app_state::SharedAppState;
+use crate::auth::{
+    logout::logout_sessions,
+    password::{hash_password, is_password_strong},
+    password_reset,
+};
+use crate::templates::validation::password::PasswordValidation;
+use askama_axum::{IntoResponse, Response};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    Form,
+};
+use secrecy::{ExposeSecret, SecretString};
+
+#[derive(Debug, serde::Deserialize)]
+pub struct PasswordReset {
+    token: String,
+    password: SecretString,
+    #[serde(rename = "password-confirm")]
+    password_confirm: SecretString,
+}
+
+/// Set a new password with a reset link, every session of the user is ended.
+#[tracing::instrument(name = "reset password", skip_all)]
+pub async fn post(
+    State(state): State<SharedAppState>,
+    Form(reset): Form<PasswordReset>,
+) -> Result<Response, StatusCode> {
+    if reset.password.expose_secret() != reset.password_confirm.expose_secret() {
+        return Ok((StatusCode::BAD_REQUEST, "password confirm error").into_response());
+    }
+    if !is_password_strong(&reset.password) {
+        return Ok((PasswordValidation {}).into_response());
+    }
+    let hashed_password = hash_password(reset.password)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let mut transaction = state
+        .db_pool
+        .begin()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let user_id = password_reset::consume_token(&mut transaction, &reset.token)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed using password reset: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let Some(user_id) = user_id else {
+        return Ok((StatusCode::BAD_REQUEST, "invalid or expired link").into_response());
+    };
+    sqlx::query!(
+        "UPDATE tbl_user SET password = $1 WHERE id = $2",
+        hashed_password.expose_secret(),
+        user_id,
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed resetting password: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    transaction.commit().await.map_err(|e| {
+        tracing::error!("Failed committing transaction: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let conn = state
+        .redis_pool
+        .get()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if let Err(e) = logout_sessions(user_id, None, conn).await {
+        tracing::error!("Failed ending the sessions: {}", e);
+    }
+    let mut headers = HeaderMap::new();
+    headers.append("HX-Redirect", "/login".parse().unwrap());
+    Ok((headers, StatusCode::OK).into_response())
+}
@@ -0,0 +1,32 @@
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult, auth::session};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Extension,
+};
+
+/// Revoke a session of the user, the empty body replaces its row.
+pub async fn delete(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    let Ok(ctx) = ctx_res else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    let mut conn = state
+        .redis_pool
+        .get()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let ended = session::end_session(&mut conn, ctx.user_id(), &id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed revoking session: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    match ended {
+        true => Ok(StatusCode::OK),
+        false => Err(StatusCode::NOT_FOUND),
+    }
+}
@@ -0,0 +1,30 @@
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult, auth::session};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{Extension, IntoResponse, Response},
+};
+
+/// Revoke every session of the user but the current one.
+pub async fn delete_others(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+) -> Result<Response, StatusCode> {
+    let Ok(ctx) = ctx_res else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    let mut conn = state
+        .redis_pool
+        .get()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    session::end_sessions(&mut conn, ctx.user_id(), Some(&ctx.session_id()))
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed revoking sessions: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let mut headers = HeaderMap::new();
+    headers.append("HX-Redirect", "/sessions".parse().unwrap());
+    Ok((headers, StatusCode::OK).into_response())
+}
@@ -0,0 +1,8 @@
+mod delete;
+mod delete_others;
+mod get;
+mod page;
+pub use delete::delete;
+pub use delete_others::delete_others;
+pub use get::get;
+pub use page::page;
@@ -0,0 +1,30 @@
+use crate::{
+    app_state::SharedAppState, auth::mw_auth::CtxResult, auth::session,
+    templates::session::SessionsPage,
+};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{Extension, IntoResponse, Response},
+};
+
+pub async fn page(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+) -> Result<Response, StatusCode> {
+    let Ok(ctx) = ctx_res else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    let mut conn = state
+        .redis_pool
+        .get()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let sessions = session::list_sessions(&mut conn, ctx.user_id(), &ctx.session_id())
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed listing sessions: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(SessionsPage { sessions }.into_response())
+}
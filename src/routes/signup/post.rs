@@ -1,4 +1,5 @@
 use crate::app_state::SharedAppState;
+use crate::auth::email_verification;
 use crate::auth::password::{hash_password, is_password_strong};
 use crate::templates::validation::password::PasswordValidation;
 use askama_axum::{IntoResponse, Response};
@@ -71,7 +72,12 @@ pub async fn post(
     })?;
 
     sqlx::query!(
-        "INSERT INTO tbl_accounting (name, user_id, description) VALUES ('default', $1, 'your first tbl_accounting')",
+        r#"WITH accounting AS (
+            INSERT INTO tbl_accounting (name, user_id, description) VALUES ('default', $1, 'your first tbl_accounting') RETURNING id
+        ), member AS (
+            INSERT INTO tbl_accounting_member (accounting_id, user_id, role) SELECT id, $1, 'owner' FROM accounting
+        )
+        UPDATE tbl_user SET current_accounting_id = (SELECT id FROM accounting) WHERE id = $1"#,
         new_user_uuid.id,
     )
     .execute(transaction.deref_mut())
@@ -85,6 +91,10 @@ pub async fn post(
         tracing::error!("Failed committing transaction: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
+    // the user can ask for another link once logged in
+    if let Err(e) = email_verification::send_link(&state, new_user_uuid.id).await {
+        tracing::error!("Failed sending confirmation mail: {}", e);
+    }
     let mut headers = HeaderMap::new();
     headers.append("HX-Redirect", "/login".parse().unwrap());
     Ok((headers, StatusCode::OK).into_response())
@@ -0,0 +1,25 @@
+use crate::model::ticket::{
+    split::{self, SplitLine},
+    TicketError,
+};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use askama_axum::{IntoResponse, Response};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use uuid::Uuid;
+
+/// The split lines of a ticket, empty when it isn't split.
+pub async fn get(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(ticket_id): Path<Uuid>,
+) -> Result<Response, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    let lines: Vec<SplitLine> = split::lines(&state.db_pool, ctx.user_id(), ticket_id).await?;
+    Ok(Json(lines).into_response())
+}
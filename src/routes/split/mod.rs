@@ -0,0 +1,4 @@
+mod get;
+mod put;
+pub use get::get;
+pub use put::put;
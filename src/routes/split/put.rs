@@ -0,0 +1,26 @@
+use crate::model::ticket::{
+    split::{self, SplitLineInput},
+    TicketError,
+};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Extension,
+    Json,
+};
+use uuid::Uuid;
+
+/// Replace the split lines of a ticket, an empty list removes the split.
+pub async fn put(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(ticket_id): Path<Uuid>,
+    Json(lines): Json<Vec<SplitLineInput>>,
+) -> Result<StatusCode, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED);
+    };
+    split::replace(&state.db_pool, ctx.user_id(), ticket_id, &lines).await?;
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,21 @@
+use crate::model::{tag, ticket::TicketError};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Extension,
+};
+use uuid::Uuid;
+
+/// Delete a tag, its tickets are kept.
+pub async fn delete(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(tag_id): Path<Uuid>,
+) -> Result<StatusCode, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED);
+    };
+    tag::delete(&state.db_pool, ctx.user_id(), tag_id).await?;
+    Ok(StatusCode::OK)
+}
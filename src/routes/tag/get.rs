@@ -0,0 +1,20 @@
+use crate::model::tag::{self, Tag};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{extract::State, http::StatusCode, response::Extension, Json};
+
+/// The tags of the user with how many tickets carry them.
+pub async fn get(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+) -> Result<Json<Vec<Tag>>, StatusCode> {
+    let Ok(ctx) = ctx_res else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    let tags = tag::list(&state.db_pool, ctx.user_id())
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed listing tags: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(tags.into())
+}
@@ -0,0 +1,29 @@
+use crate::model::{tag, ticket::TicketError};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Extension,
+    Json,
+};
+use uuid::Uuid;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct MergeInput {
+    /// The tag kept, the one of the path is deleted.
+    into: Uuid,
+}
+
+/// Move the tickets of a tag to another one and delete it.
+pub async fn merge(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(tag_id): Path<Uuid>,
+    Json(input): Json<MergeInput>,
+) -> Result<StatusCode, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED);
+    };
+    tag::merge(&state.db_pool, ctx.user_id(), tag_id, input.into).await?;
+    Ok(StatusCode::OK)
+}
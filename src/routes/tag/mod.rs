@@ -0,0 +1,10 @@
+mod delete;
+mod get;
+mod merge;
+mod put;
+mod suggest;
+pub use delete::delete;
+pub use get::get;
+pub use merge::merge;
+pub use put::put;
+pub use suggest::suggest;
@@ -0,0 +1,28 @@
+use crate::model::{tag, ticket::TicketError};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Extension,
+    Json,
+};
+use uuid::Uuid;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct TagInput {
+    name: String,
+}
+
+/// Rename a tag.
+pub async fn put(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(tag_id): Path<Uuid>,
+    Json(input): Json<TagInput>,
+) -> Result<StatusCode, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED);
+    };
+    tag::rename(&state.db_pool, ctx.user_id(), tag_id, &input.name).await?;
+    Ok(StatusCode::OK)
+}
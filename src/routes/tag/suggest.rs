@@ -0,0 +1,32 @@
+use crate::model::tag;
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Extension,
+    Json,
+};
+
+#[derive(Debug, serde::Deserialize)]
+pub struct SuggestQuery {
+    #[serde(default)]
+    q: String,
+}
+
+/// Names of the tags matching what was typed so far.
+pub async fn suggest(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Query(query): Query<SuggestQuery>,
+) -> Result<Json<Vec<String>>, StatusCode> {
+    let Ok(ctx) = ctx_res else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    let names = tag::suggest(&state.db_pool, ctx.user_id(), &query.q)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed suggesting tags: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(names.into())
+}
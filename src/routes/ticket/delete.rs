@@ -0,0 +1,24 @@
+use crate::model::{
+    attachment,
+    ticket::{self, TicketError},
+};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Extension,
+};
+use uuid::Uuid;
+
+pub async fn delete(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(ticket_id): Path<Uuid>,
+) -> Result<StatusCode, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED);
+    };
+    let storage_keys = ticket::delete(&state.db_pool, ctx.user_id(), ticket_id).await?;
+    attachment::remove_blobs(state.blob_store.as_ref(), storage_keys).await;
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,137 @@
+use crate::model::ticket::{
+    export::{self, ExportRow},
+    filter::TicketFilter,
+};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{Extension, IntoResponse, Response},
+    BoxError,
+};
+use futures_util::TryStreamExt;
+use tokio::sync::mpsc;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    /// One JSON object per line.
+    Ndjson,
+}
+
+impl ExportFormat {
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            Self::Csv => "tickets.csv",
+            Self::Ndjson => "tickets.ndjson",
+        }
+    }
+
+    fn header(self) -> Result<Vec<u8>, BoxError> {
+        match self {
+            Self::Csv => {
+                let mut writer = csv::Writer::from_writer(Vec::new());
+                writer.write_record([
+                    "id",
+                    "created_at",
+                    "accounting",
+                    "direction",
+                    "amount",
+                    "currency",
+                    "category",
+                    "description",
+                    "tags",
+                ])?;
+                Ok(writer.into_inner()?)
+            }
+            Self::Ndjson => Ok(Vec::new()),
+        }
+    }
+
+    fn encode(self, row: &ExportRow) -> Result<Vec<u8>, BoxError> {
+        match self {
+            Self::Csv => {
+                let mut writer = csv::WriterBuilder::new()
+                    .has_headers(false)
+                    .from_writer(Vec::new());
+                writer.serialize(row)?;
+                Ok(writer.into_inner()?)
+            }
+            Self::Ndjson => {
+                let mut line = serde_json::to_vec(row)?;
+                line.push(b'\n');
+                Ok(line)
+            }
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    format: ExportFormat,
+}
+
+/// Download the tickets matching the same filters as `GET /ticket`.
+///
+/// Rows are streamed straight from the database, so a failure halfway through cuts the body
+/// short instead of answering with an error status.
+pub async fn export(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Query(query): Query<ExportQuery>,
+    Query(filter): Query<TicketFilter>,
+) -> Response {
+    let Ok(ctx) = ctx_res else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let format = query.format;
+    let pool = state.db_pool.clone();
+    let mut builder = export::query(ctx.user_id(), &filter);
+    let (sender, receiver) = mpsc::channel::<Result<Vec<u8>, BoxError>>(32);
+    tokio::spawn(async move {
+        if sender.send(format.header()).await.is_err() {
+            return;
+        }
+        let mut rows = builder.build_query_as::<ExportRow>().fetch(&pool);
+        loop {
+            let chunk = match rows.try_next().await {
+                Ok(Some(row)) => format.encode(&row),
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::error!("Failed exporting tickets: {}", e);
+                    Err(e.into())
+                }
+            };
+            let failed = chunk.is_err();
+            // the client went away or the body is already broken
+            if sender.send(chunk).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+    let body = futures_util::stream::unfold(receiver, |mut receiver| async {
+        receiver.recv().await.map(|chunk| (chunk, receiver))
+    });
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", format.file_name()),
+            ),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response()
+}
@@ -1,4 +1,10 @@
+mod delete;
+mod export;
 mod get;
 mod post;
+mod put;
+pub use delete::delete;
+pub use export::export;
 pub use get::get;
 pub use post::post;
+pub use put::put;
@@ -0,0 +1,22 @@
+use crate::model::ticket::{self, TicketError, TicketInput};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Extension,
+    Json,
+};
+use uuid::Uuid;
+
+pub async fn put(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(ticket_id): Path<Uuid>,
+    Json(ticket): Json<TicketInput>,
+) -> Result<StatusCode, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED);
+    };
+    ticket::update(&state.db_pool, ctx.user_id(), ticket_id, &ticket).await?;
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,30 @@
+use crate::model::{
+    category,
+    ticket::{self, TicketError},
+};
+use crate::templates::ticket::TicketEdit;
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use askama_axum::{IntoResponse, Response};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension,
+};
+use uuid::Uuid;
+
+pub async fn edit(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(ticket_id): Path<Uuid>,
+) -> Result<Response, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    let ticket = ticket::get(&state.db_pool, ctx.user_id(), ticket_id).await?;
+    let categories = category::usable_paths(&state.db_pool, ctx.user_id()).await?;
+    Ok(TicketEdit {
+        ticket: ticket.into(),
+        categories,
+    }
+    .into_response())
+}
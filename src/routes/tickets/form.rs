@@ -0,0 +1,66 @@
+use crate::model::{
+    direction::TicketDirection,
+    ticket::{TicketError, TicketInput},
+};
+use crate::templates::validation::form::FormValidation;
+use askama_axum::{IntoResponse, Response};
+use axum::http::HeaderMap;
+use sqlx::types::Decimal;
+use uuid::Uuid;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct TicketForm {
+    direction: TicketDirection,
+    amount: Decimal,
+    type_id: Uuid,
+    description: String,
+    created_at: chrono::NaiveDate,
+    /// Left empty for the currency of the book.
+    #[serde(default)]
+    currency: String,
+    /// Tag names separated by commas.
+    tags: Option<String>,
+}
+
+impl From<TicketForm> for TicketInput {
+    fn from(form: TicketForm) -> Self {
+        Self {
+            direction: form.direction,
+            amount: form.amount,
+            type_id: form.type_id,
+            description: form.description,
+            created_at: Some(form.created_at.and_time(chrono::NaiveTime::MIN).and_utc()),
+            accounting_id: None,
+            currency: Some(form.currency).filter(|currency| !currency.trim().is_empty()),
+            tags: form.tags.map(|tags| {
+                tags.split(',')
+                    .filter(|tag| !tag.trim().is_empty())
+                    .map(str::to_string)
+                    .collect()
+            }),
+        }
+    }
+}
+
+/// Render a `TicketError` for htmx: validation messages are swapped into the `target`
+/// paragraph of the form instead of the row.
+pub fn error_response(error: TicketError, target: &str) -> Response {
+    match error {
+        TicketError::Invalid(message) => {
+            let mut headers = HeaderMap::new();
+            headers.append("HX-Retarget", format!("#{target}").parse().unwrap());
+            headers.append("HX-Reswap", "outerHTML".parse().unwrap());
+            (
+                headers,
+                FormValidation {
+                    target,
+                    valid_message: "",
+                    invalid_message: message,
+                    is_valid: false,
+                },
+            )
+                .into_response()
+        }
+        error => error.into_response(),
+    }
+}
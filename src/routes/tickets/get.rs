@@ -0,0 +1,32 @@
+use crate::model::{
+    category,
+    ticket::{self, filter::TicketFilter, pagination::TicketPagination, TicketError},
+};
+use crate::templates::{ticket::Ticket, TicketPage};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use askama_axum::{IntoResponse, Response};
+use axum::{extract::State, http::StatusCode, Extension};
+
+pub async fn get(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+) -> Result<Response, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    let page = ticket::list(
+        &state.db_pool,
+        ctx.user_id(),
+        &TicketFilter::default(),
+        &TicketPagination::default(),
+    )
+    .await?;
+    let categories = category::usable_paths(&state.db_pool, ctx.user_id()).await?;
+    Ok(TicketPage {
+        tickets: page.tickets.into_iter().map(Ticket::from).collect(),
+        next_cursor: page.next_cursor,
+        categories,
+        today: chrono::Utc::now().format("%Y-%m-%d").to_string(),
+    }
+    .into_response())
+}
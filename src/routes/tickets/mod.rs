@@ -0,0 +1,15 @@
+mod delete;
+mod edit;
+pub(crate) mod form;
+mod get;
+mod post;
+mod put;
+mod row;
+mod rows;
+pub use delete::delete;
+pub use edit::edit;
+pub use get::get;
+pub use post::post;
+pub use put::put;
+pub use row::row;
+pub use rows::rows;
@@ -0,0 +1,24 @@
+use super::form::{error_response, TicketForm};
+use crate::model::ticket;
+use crate::templates::ticket::Ticket;
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use askama_axum::{IntoResponse, Response};
+use axum::{extract::State, http::StatusCode, Extension, Form};
+
+pub async fn post(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Form(form): Form<TicketForm>,
+) -> Response {
+    let Ok(ctx) = ctx_res else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let created = match ticket::create(&state.db_pool, ctx.user_id(), &form.into()).await {
+        Ok(ticket_id) => ticket::get(&state.db_pool, ctx.user_id(), ticket_id).await,
+        Err(e) => Err(e),
+    };
+    match created {
+        Ok(ticket) => Ticket::from(ticket).into_response(),
+        Err(e) => error_response(e, "ticket-error"),
+    }
+}
@@ -0,0 +1,39 @@
+use super::form::{error_response, TicketForm};
+use crate::model::ticket::{self, TicketInput};
+use crate::templates::ticket::Ticket;
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use askama_axum::{IntoResponse, Response};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Form,
+};
+use uuid::Uuid;
+
+pub async fn put(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(ticket_id): Path<Uuid>,
+    Form(form): Form<TicketForm>,
+) -> Response {
+    let Ok(ctx) = ctx_res else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let mut input: TicketInput = form.into();
+    let updated = async {
+        // the form only edits the day, the ticket keeps its time
+        let stored = ticket::get(&state.db_pool, ctx.user_id(), ticket_id).await?;
+        input.created_at = input.created_at.map(|date| {
+            date.date_naive()
+                .and_time(stored.created_at.time())
+                .and_utc()
+        });
+        ticket::update(&state.db_pool, ctx.user_id(), ticket_id, &input).await?;
+        ticket::get(&state.db_pool, ctx.user_id(), ticket_id).await
+    };
+    let updated = updated.await;
+    match updated {
+        Ok(ticket) => Ticket::from(ticket).into_response(),
+        Err(e) => error_response(e, &format!("ticket-{ticket_id}-error")),
+    }
+}
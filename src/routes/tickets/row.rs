@@ -0,0 +1,22 @@
+use crate::model::ticket::{self, TicketError};
+use crate::templates::ticket::Ticket;
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use askama_axum::{IntoResponse, Response};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension,
+};
+use uuid::Uuid;
+
+pub async fn row(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(ticket_id): Path<Uuid>,
+) -> Result<Response, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    let ticket = ticket::get(&state.db_pool, ctx.user_id(), ticket_id).await?;
+    Ok(Ticket::from(ticket).into_response())
+}
@@ -0,0 +1,27 @@
+use crate::model::ticket::{self, filter::TicketFilter, pagination::TicketPagination, TicketError};
+use crate::templates::ticket::{Ticket, TicketRows};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use askama_axum::{IntoResponse, Response};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Extension,
+};
+
+/// The rows following `cursor`, loaded as the user scrolls down the ticket page.
+pub async fn rows(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Query(filter): Query<TicketFilter>,
+    Query(pagination): Query<TicketPagination>,
+) -> Result<Response, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    let page = ticket::list(&state.db_pool, ctx.user_id(), &filter, &pagination).await?;
+    Ok(TicketRows {
+        tickets: page.tickets.into_iter().map(Ticket::from).collect(),
+        next_cursor: page.next_cursor,
+    }
+    .into_response())
+}
@@ -0,0 +1,32 @@
+use crate::{
+    app_state::SharedAppState,
+    auth::{
+        mw_auth::CtxResult,
+        totp::{self, TotpError},
+    },
+    templates::totp::RecoveryCodes,
+};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{Extension, IntoResponse, Response},
+    Form,
+};
+
+#[derive(Debug, serde::Deserialize)]
+pub struct CodeInput {
+    pub code: String,
+}
+
+/// Turn on two-factor authentication with a first code, the recovery codes are shown once.
+pub async fn confirm(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Form(input): Form<CodeInput>,
+) -> Result<Response, TotpError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    let codes = totp::confirm_enrollment(&state.db_pool, ctx.user_id(), &input.code).await?;
+    Ok(RecoveryCodes { codes }.into_response())
+}
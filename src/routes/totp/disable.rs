@@ -0,0 +1,47 @@
+use super::confirm::CodeInput;
+use crate::{
+    app_state::SharedAppState,
+    auth::{
+        mw_auth::CtxResult,
+        throttle::{self, Account, ThrottleError},
+        totp::{self, TotpError},
+    },
+};
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, StatusCode},
+    response::{Extension, IntoResponse, Response},
+    Form,
+};
+use std::net::SocketAddr;
+
+pub async fn disable(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Form(input): Form<CodeInput>,
+) -> Result<Response, TotpError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    // wrong codes count against the account like wrong logins
+    let mut conn = state
+        .redis_pool
+        .get()
+        .await
+        .map_err(|e| ThrottleError::UnexpectedError(e.into()))?;
+    let throttle = &state.login_throttle;
+    let account = Account::User(ctx.user_id());
+    throttle::check(&mut conn, throttle, &account, addr.ip()).await?;
+    match totp::disable(&state.db_pool, ctx.user_id(), &input.code).await {
+        Err(TotpError::Invalid(message)) => {
+            throttle::record_failure(&mut conn, throttle, &account, addr.ip()).await?;
+            return Err(TotpError::Invalid(message));
+        }
+        result => result?,
+    };
+    throttle::record_success(&mut conn, throttle, &account).await?;
+    let mut headers = HeaderMap::new();
+    headers.append("HX-Redirect", "/totp".parse().unwrap());
+    Ok((headers, StatusCode::OK).into_response())
+}
@@ -0,0 +1,35 @@
+use crate::{
+    app_state::SharedAppState,
+    auth::{
+        mw_auth::CtxResult,
+        totp::{self, TotpError},
+    },
+    templates::totp::TotpEnroll,
+};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{Extension, IntoResponse, Response},
+};
+
+/// Make a secret to scan, two-factor authentication is on once a code confirms it.
+pub async fn enroll(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+) -> Result<Response, TotpError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    let username =
+        sqlx::query_scalar!("SELECT username FROM tbl_user WHERE id = $1", ctx.user_id())
+            .fetch_one(&state.db_pool)
+            .await?;
+    let secret = totp::start_enrollment(&state.db_pool, ctx.user_id()).await?;
+    let uri = totp::otpauth_uri(&username, &secret);
+    Ok(TotpEnroll {
+        qr_svg: totp::qr_svg(&uri),
+        uri,
+        secret,
+    }
+    .into_response())
+}
@@ -0,0 +1,31 @@
+use crate::{
+    app_state::SharedAppState, auth::mw_auth::CtxResult, auth::totp, templates::totp::TotpPage,
+};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{Extension, IntoResponse, Response},
+};
+
+pub async fn get(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+) -> Result<Response, StatusCode> {
+    let Ok(ctx) = ctx_res else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    let status = async {
+        let enabled = totp::is_enabled(&state.db_pool, ctx.user_id()).await?;
+        let left = totp::recovery_codes_left(&state.db_pool, ctx.user_id()).await?;
+        Ok::<_, sqlx::Error>((enabled, left))
+    };
+    let (enabled, recovery_codes_left) = status.await.map_err(|e| {
+        tracing::error!("Failed fetching two-factor status: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(TotpPage {
+        enabled,
+        recovery_codes_left,
+    }
+    .into_response())
+}
@@ -0,0 +1,10 @@
+mod confirm;
+mod disable;
+mod enroll;
+mod get;
+mod recovery_codes;
+pub use confirm::confirm;
+pub use disable::disable;
+pub use enroll::enroll;
+pub use get::get;
+pub use recovery_codes::recovery_codes;
@@ -0,0 +1,47 @@
+use super::confirm::CodeInput;
+use crate::{
+    app_state::SharedAppState,
+    auth::{
+        mw_auth::CtxResult,
+        throttle::{self, Account, ThrottleError},
+        totp::{self, TotpError},
+    },
+    templates::totp::RecoveryCodes,
+};
+use axum::{
+    extract::{ConnectInfo, State},
+    http::StatusCode,
+    response::{Extension, IntoResponse, Response},
+    Form,
+};
+use std::net::SocketAddr;
+
+/// Replace the recovery codes, the old ones stop working.
+pub async fn recovery_codes(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Form(input): Form<CodeInput>,
+) -> Result<Response, TotpError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    let mut conn = state
+        .redis_pool
+        .get()
+        .await
+        .map_err(|e| ThrottleError::UnexpectedError(e.into()))?;
+    let throttle = &state.login_throttle;
+    let account = Account::User(ctx.user_id());
+    throttle::check(&mut conn, throttle, &account, addr.ip()).await?;
+    let codes =
+        match totp::regenerate_recovery_codes(&state.db_pool, ctx.user_id(), &input.code).await {
+            Err(TotpError::Invalid(message)) => {
+                throttle::record_failure(&mut conn, throttle, &account, addr.ip()).await?;
+                return Err(TotpError::Invalid(message));
+            }
+            result => result?,
+        };
+    throttle::record_success(&mut conn, throttle, &account).await?;
+    Ok(RecoveryCodes { codes }.into_response())
+}
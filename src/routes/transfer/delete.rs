@@ -0,0 +1,24 @@
+use crate::model::{attachment, ticket, transfer};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension,
+};
+use uuid::Uuid;
+
+/// Delete both sides of a transfer, like deleting either of them with `DELETE /ticket/:id`.
+pub async fn delete(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(transfer_id): Path<Uuid>,
+) -> Result<StatusCode, ticket::TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED);
+    };
+    let transfer = transfer::get(&state.db_pool, ctx.user_id(), transfer_id).await?;
+    let storage_keys =
+        ticket::delete(&state.db_pool, ctx.user_id(), transfer.out_ticket_id).await?;
+    attachment::remove_blobs(state.blob_store.as_ref(), storage_keys).await;
+    Ok(StatusCode::OK)
+}
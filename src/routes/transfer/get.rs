@@ -0,0 +1,24 @@
+use crate::model::{
+    ticket::TicketError,
+    transfer::{self, Transfer},
+};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use askama_axum::{IntoResponse, Response};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use uuid::Uuid;
+
+pub async fn get(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Path(transfer_id): Path<Uuid>,
+) -> Result<Response, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    let transfer: Transfer = transfer::get(&state.db_pool, ctx.user_id(), transfer_id).await?;
+    Ok(Json(transfer).into_response())
+}
@@ -0,0 +1,6 @@
+mod delete;
+mod get;
+mod post;
+pub use delete::delete;
+pub use get::get;
+pub use post::post;
@@ -0,0 +1,21 @@
+use crate::model::{
+    ticket::TicketError,
+    transfer::{self, Transfer, TransferInput},
+};
+use crate::{app_state::SharedAppState, auth::mw_auth::CtxResult};
+use askama_axum::{IntoResponse, Response};
+use axum::{extract::State, http::StatusCode, Extension, Json};
+
+/// Move money from one book to another.
+pub async fn post(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+    Json(input): Json<TransferInput>,
+) -> Result<Response, TicketError> {
+    let Ok(ctx) = ctx_res else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    let transfer_id = transfer::create(&state.db_pool, ctx.user_id(), &input).await?;
+    let transfer: Transfer = transfer::get(&state.db_pool, ctx.user_id(), transfer_id).await?;
+    Ok(Json(transfer).into_response())
+}
@@ -0,0 +1,26 @@
+use crate::{app_state::SharedAppState, auth::email_verification, templates::VerifyEmailPage};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+#[derive(Debug, serde::Deserialize)]
+pub struct VerifyQuery {
+    #[serde(default)]
+    token: String,
+}
+
+/// Where the confirmation link leads, it works without being logged in.
+pub async fn get(
+    State(state): State<SharedAppState>,
+    Query(query): Query<VerifyQuery>,
+) -> Result<Response, StatusCode> {
+    let verified = email_verification::verify(&state.db_pool, &state.auth_secret, &query.token)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed verifying email: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(VerifyEmailPage { verified }.into_response())
+}
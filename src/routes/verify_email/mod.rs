@@ -0,0 +1,4 @@
+mod get;
+mod resend;
+pub use get::get;
+pub use resend::resend;
@@ -0,0 +1,23 @@
+use crate::{
+    app_state::SharedAppState,
+    auth::{email_verification, mw_auth::CtxResult},
+};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{Extension, IntoResponse, Response},
+};
+
+/// Mail another confirmation link to the user.
+pub async fn resend(
+    State(state): State<SharedAppState>,
+    Extension(ctx_res): Extension<CtxResult>,
+) -> Response {
+    let Ok(ctx) = ctx_res else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    match email_verification::send_link(&state, ctx.user_id()).await {
+        Ok(()) => "a new confirmation link has been sent".into_response(),
+        Err(e) => e.into_response(),
+    }
+}
@@ -0,0 +1,23 @@
+use crate::model::recurring;
+use sqlx::PgPool;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// How often due recurring tickets are looked for.
+const RECURRING_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Create the tickets of the recurring rules in the background.
+pub fn spawn_recurring(pool: PgPool) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(RECURRING_INTERVAL);
+        loop {
+            interval.tick().await;
+            let today = chrono::Utc::now().date_naive();
+            match recurring::materialize_due(&pool, today).await {
+                Ok(0) => {}
+                Ok(created) => tracing::info!("Created {} recurring tickets", created),
+                Err(e) => tracing::error!("Failed creating recurring tickets: {}", e),
+            }
+        }
+    })
+}
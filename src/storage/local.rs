@@ -0,0 +1,51 @@
+use super::BlobStore;
+use axum::{async_trait, body::Bytes};
+use std::io::{Error, ErrorKind};
+use std::path::PathBuf;
+
+/// Keeps every blob as a file of a local directory.
+pub struct LocalStore {
+    dir: PathBuf,
+}
+
+impl LocalStore {
+    /// Create the directory if needed.
+    pub fn new(dir: PathBuf) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// Keys are made by the app, anything that could leave the directory is refused.
+    fn path(&self, key: &str) -> std::io::Result<PathBuf> {
+        if key.is_empty()
+            || !key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(Error::new(ErrorKind::InvalidInput, "invalid blob key"));
+        }
+        Ok(self.dir.join(key))
+    }
+}
+
+#[async_trait]
+impl BlobStore for LocalStore {
+    async fn put(&self, key: &str, data: Bytes) -> std::io::Result<()> {
+        let path = self.path(key)?;
+        // readers never see a partly written file
+        let partial = path.with_extension("partial");
+        tokio::fs::write(&partial, &data).await?;
+        tokio::fs::rename(&partial, &path).await
+    }
+
+    async fn get(&self, key: &str) -> std::io::Result<Bytes> {
+        Ok(tokio::fs::read(self.path(key)?).await?.into())
+    }
+
+    async fn delete(&self, key: &str) -> std::io::Result<()> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+}
@@ -0,0 +1,26 @@
+//! Where the content of attachments is kept, the database only stores their key.
+mod local;
+
+pub use local::LocalStore;
+
+use crate::configuration::StorageSettings;
+use axum::{async_trait, body::Bytes};
+use std::sync::Arc;
+
+pub type SharedBlobStore = Arc<dyn BlobStore>;
+
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    /// Store `data` under `key`, replacing what was there.
+    async fn put(&self, key: &str, data: Bytes) -> std::io::Result<()>;
+    /// Fails with `ErrorKind::NotFound` when nothing is stored under `key`.
+    async fn get(&self, key: &str) -> std::io::Result<Bytes>;
+    /// Deleting a missing key is not an error.
+    async fn delete(&self, key: &str) -> std::io::Result<()>;
+}
+
+pub fn from_settings(settings: &StorageSettings) -> std::io::Result<SharedBlobStore> {
+    match settings {
+        StorageSettings::Local(local) => Ok(Arc::new(LocalStore::new(local.dir.clone())?)),
+    }
+}
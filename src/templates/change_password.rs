@@ -0,0 +1,5 @@
+use askama::Template;
+
+#[derive(Template)]
+#[template(path = "change_password.html")]
+pub struct ChangePasswordPage {}
@@ -0,0 +1,13 @@
+use askama::Template;
+
+#[derive(Template)]
+#[template(path = "forgot.html")]
+pub struct ForgotPage {}
+
+#[derive(Template)]
+#[template(path = "reset_password.html")]
+pub struct ResetPasswordPage {
+    pub token: String,
+    /// Whether the token can still be used, the form is only shown then.
+    pub valid: bool,
+}
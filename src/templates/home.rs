@@ -1,7 +1,45 @@
+use crate::model::budget::{BudgetPeriod, BudgetStatus};
 use askama::Template;
+use sqlx::types::Decimal;
 
 #[derive(Template)]
 #[template(path = "home.html")]
 pub struct HomePage {
     pub user: Option<String>,
+    pub budgets: Vec<Budget>,
+    /// Asks for the confirmation of the email when false.
+    pub email_verified: bool,
+}
+
+pub struct Budget {
+    pub name: String,
+    pub period: &'static str,
+    pub amount: Decimal,
+    pub currency: String,
+    pub spent: Decimal,
+    pub percent_used: Decimal,
+    /// Width of the progress bar, capped at 100.
+    pub width: Decimal,
+    pub over_budget: bool,
+    /// Lines spent in a currency without a known rate, not counted.
+    pub unconverted: i64,
+}
+
+impl From<BudgetStatus> for Budget {
+    fn from(value: BudgetStatus) -> Self {
+        Self {
+            name: value.type_name,
+            period: match value.period {
+                BudgetPeriod::Month => "this month",
+                BudgetPeriod::Year => "this year",
+            },
+            amount: value.amount,
+            currency: value.currency,
+            spent: value.spent,
+            width: value.percent_used.min(Decimal::ONE_HUNDRED),
+            over_budget: value.over_budget,
+            percent_used: value.percent_used,
+            unconverted: value.unconverted,
+        }
+    }
 }
@@ -0,0 +1,79 @@
+use crate::model::{category::CategoryPath, direction::TicketDirection, import::StagedRow};
+use askama::Template;
+use uuid::Uuid;
+
+#[derive(Template)]
+#[template(path = "import/import_page.html")]
+pub struct ImportPage {
+    pub accountings: Vec<AccountingOption>,
+    pub categories: Vec<CategoryPath>,
+}
+
+pub struct AccountingOption {
+    pub id: Uuid,
+    pub name: String,
+    pub current: bool,
+}
+
+#[derive(Template)]
+#[template(path = "import/import_preview.html")]
+pub struct ImportPreview {
+    pub id: Uuid,
+    pub rows: Vec<ImportRow>,
+    pub new: usize,
+    pub duplicates: usize,
+    pub errors: usize,
+}
+
+impl ImportPreview {
+    pub fn new(id: Uuid, rows: Vec<StagedRow>) -> Self {
+        let errors = rows.iter().filter(|row| row.error.is_some()).count();
+        let duplicates = rows.iter().filter(|row| row.duplicate).count();
+        Self {
+            id,
+            new: rows.len() - errors - duplicates,
+            duplicates,
+            errors,
+            rows: rows.into_iter().map(ImportRow::from).collect(),
+        }
+    }
+}
+
+pub struct ImportRow {
+    pub line: i32,
+    pub created_at: String,
+    pub direction: Option<TicketDirection>,
+    pub amount: String,
+    pub description: String,
+    pub category: String,
+    pub error: Option<String>,
+    pub duplicate: bool,
+}
+
+impl From<StagedRow> for ImportRow {
+    fn from(row: StagedRow) -> Self {
+        Self {
+            line: row.line,
+            created_at: row
+                .created_at
+                .map(|created_at| created_at.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+            direction: row.direction,
+            amount: match (row.amount, row.currency) {
+                (Some(amount), Some(currency)) => format!("{amount} {currency}"),
+                (Some(amount), None) => amount.to_string(),
+                (None, _) => String::new(),
+            },
+            description: row.description.unwrap_or_default(),
+            category: row.category.unwrap_or_default(),
+            error: row.error,
+            duplicate: row.duplicate,
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "import/import_done.html")]
+pub struct ImportDone {
+    pub created: u64,
+}
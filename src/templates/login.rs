@@ -3,3 +3,7 @@ use askama::Template;
 #[derive(Template)]
 #[template(path = "login.html")]
 pub struct LoginPage {}
+
+#[derive(Template)]
+#[template(path = "login_totp.html")]
+pub struct LoginTotpPage {}
@@ -1,9 +1,18 @@
+mod change_password;
+mod forgot;
 mod home;
+pub mod import;
 mod login;
+pub mod session;
 mod signup;
-mod ticket;
+pub mod ticket;
+pub mod totp;
+mod verify_email;
+pub use change_password::ChangePasswordPage;
+pub use forgot::{ForgotPage, ResetPasswordPage};
 pub use home::HomePage;
-pub use login::LoginPage;
+pub use login::{LoginPage, LoginTotpPage};
 pub use signup::SignupPage;
 pub use ticket::TicketPage;
+pub use verify_email::VerifyEmailPage;
 pub mod validation;
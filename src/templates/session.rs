@@ -0,0 +1,8 @@
+use crate::auth::session::SessionInfo;
+use askama::Template;
+
+#[derive(Template)]
+#[template(path = "sessions.html")]
+pub struct SessionsPage {
+    pub sessions: Vec<SessionInfo>,
+}
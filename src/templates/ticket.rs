@@ -1,18 +1,63 @@
+use crate::model::{category::CategoryPath, direction::TicketDirection, ticket};
 use askama::Template;
+use sqlx::types::Decimal;
 use uuid::Uuid;
 
 #[derive(Template)]
 #[template(path = "ticket/ticket_page.html")]
 pub struct TicketPage {
     pub tickets: Vec<Ticket>,
+    pub next_cursor: Option<String>,
+    pub categories: Vec<CategoryPath>,
+    pub today: String,
+}
+
+/// A page of rows, followed by the row loading the next page when it's revealed.
+#[derive(Template)]
+#[template(path = "ticket/ticket_rows.html")]
+pub struct TicketRows {
+    pub tickets: Vec<Ticket>,
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Template)]
 #[template(path = "ticket/ticket.html")]
 pub struct Ticket {
-    pub amount: f32,
+    pub id: Uuid,
+    pub direction: TicketDirection,
+    pub amount: Decimal,
+    pub currency: String,
     pub description: String,
     pub type_str: String,
     pub type_id: Uuid,
-    pub id: Uuid,
+    pub split: bool,
+    pub tags: Vec<String>,
+    pub created_at: String,
+    /// Username of the member who added the ticket.
+    pub created_by: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "ticket/ticket_edit.html")]
+pub struct TicketEdit {
+    pub ticket: Ticket,
+    pub categories: Vec<CategoryPath>,
+}
+
+impl From<ticket::Ticket> for Ticket {
+    fn from(ticket: ticket::Ticket) -> Self {
+        Self {
+            id: ticket.id,
+            direction: ticket.direction,
+            amount: ticket.amount,
+            currency: ticket.currency,
+            description: ticket.description,
+            type_str: ticket.r#type,
+            type_id: ticket.type_id,
+            split: ticket.split,
+            tags: ticket.tags,
+            created_at: ticket.created_at.format("%Y-%m-%d").to_string(),
+            created_by: ticket.created_by,
+        }
+    }
 }
@@ -0,0 +1,23 @@
+use askama::Template;
+
+#[derive(Template)]
+#[template(path = "totp/totp.html")]
+pub struct TotpPage {
+    pub enabled: bool,
+    pub recovery_codes_left: i64,
+}
+
+#[derive(Template)]
+#[template(path = "totp/enroll.html")]
+pub struct TotpEnroll {
+    pub secret: String,
+    /// The `otpauth://` URI of the secret.
+    pub uri: String,
+    pub qr_svg: String,
+}
+
+#[derive(Template)]
+#[template(path = "totp/recovery_codes.html")]
+pub struct RecoveryCodes {
+    pub codes: Vec<String>,
+}
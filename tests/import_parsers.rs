@@ -0,0 +1,323 @@
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use ticket_app::model::{
+    direction::TicketDirection,
+    import::{
+        camt,
+        csv::{self, CsvMapping},
+        ofx, qif, Entry, ParsedRow,
+    },
+};
+
+fn entry(row: &ParsedRow) -> &Entry {
+    row.entry.as_ref().expect("a valid row")
+}
+
+fn error(row: &ParsedRow) -> &str {
+    row.entry.as_ref().expect_err("an invalid row")
+}
+
+fn decimal(amount: &str) -> Decimal {
+    Decimal::from_str(amount).unwrap()
+}
+
+fn mapping(mapping: serde_json::Value) -> CsvMapping {
+    serde_json::from_value(mapping).unwrap()
+}
+
+#[test]
+fn csv_statement_with_header() {
+    let mapping = mapping(serde_json::json!({
+        "delimiter": ";",
+        "has_header": true,
+        "date_column": "buchungstag",
+        "date_format": "%d.%m.%Y",
+        "amount_column": "Betrag",
+        "description_column": "Verwendungszweck",
+        "category_column": "Kategorie",
+        "decimal_comma": true,
+    }));
+    let rows = csv::parse(include_bytes!("fixtures/statement_semicolon.csv"), &mapping).unwrap();
+    assert_eq!(rows.len(), 6);
+
+    let rent = entry(&rows[0]);
+    assert_eq!(rows[0].line, 2);
+    assert_eq!(rent.direction, TicketDirection::Out);
+    assert_eq!(rent.amount, decimal("1234.56"));
+    assert_eq!(rent.created_at.to_rfc3339(), "2026-09-02T00:00:00+00:00");
+    assert_eq!(rent.description, "Miete September");
+    assert_eq!(rent.category.as_deref(), Some("Housing"));
+
+    // identical rows are both kept, with different ids
+    let (first, second) = (entry(&rows[1]), entry(&rows[2]));
+    assert_eq!(first.amount, decimal("4.50"));
+    assert_eq!(first.category, None);
+    assert_ne!(first.external_id, second.external_id);
+
+    let salary = entry(&rows[3]);
+    assert_eq!(salary.direction, TicketDirection::In);
+    assert_eq!(salary.amount, decimal("2500.00"));
+
+    assert_eq!(error(&rows[4]), "invalid date '31.09.2026'");
+    assert_eq!(error(&rows[5]), "missing amount");
+}
+
+#[test]
+fn csv_statement_by_position() {
+    let mapping = mapping(serde_json::json!({
+        "date_column": "1",
+        "amount_column": "2",
+        "amount_sign": "direction_column",
+        "direction_column": "3",
+        "description_column": "4",
+    }));
+    // `,` and `%Y-%m-%d` are the defaults
+    assert_eq!(mapping.delimiter, ',');
+    assert_eq!(mapping.date_format, "%Y-%m-%d");
+    let rows = csv::parse(
+        include_bytes!("fixtures/statement_positional.csv"),
+        &mapping,
+    )
+    .unwrap();
+    assert_eq!(rows.len(), 3);
+
+    let lunch = entry(&rows[0]);
+    assert_eq!(rows[0].line, 1);
+    assert_eq!(lunch.direction, TicketDirection::Out);
+    assert_eq!(lunch.amount, decimal("12.50"));
+    assert_eq!(lunch.created_at.to_rfc3339(), "2026-09-01T00:00:00+00:00");
+    assert_eq!(lunch.description, "Lunch");
+
+    assert_eq!(entry(&rows[1]).direction, TicketDirection::In);
+    assert_eq!(error(&rows[2]), "invalid direction 'sideways'");
+}
+
+#[test]
+fn csv_rejects_unknown_columns() {
+    let data = include_bytes!("fixtures/statement_semicolon.csv");
+    let missing = mapping(serde_json::json!({
+        "delimiter": ";",
+        "has_header": true,
+        "date_column": "Buchungstag",
+        "amount_column": "Amount",
+    }));
+    assert_eq!(
+        csv::parse(data, &missing).unwrap_err(),
+        "unknown amount column"
+    );
+    // a header name can't be found without a header
+    let headless = mapping(serde_json::json!({
+        "delimiter": ";",
+        "date_column": "Buchungstag",
+        "amount_column": "2",
+    }));
+    assert_eq!(
+        csv::parse(data, &headless).unwrap_err(),
+        "unknown date column"
+    );
+    let unsigned = mapping(serde_json::json!({
+        "date_column": "1",
+        "amount_column": "2",
+        "amount_sign": "direction_column",
+    }));
+    assert!(csv::parse(data, &unsigned).is_err());
+}
+
+#[test]
+fn csv_ids_are_stable() {
+    let mapping = mapping(serde_json::json!({
+        "delimiter": ";",
+        "has_header": true,
+        "date_column": "Buchungstag",
+        "date_format": "%d.%m.%Y",
+        "amount_column": "Betrag",
+        "description_column": "Verwendungszweck",
+        "decimal_comma": true,
+    }));
+    let data = include_bytes!("fixtures/statement_semicolon.csv");
+    let ids = |rows: Vec<ParsedRow>| {
+        rows.into_iter()
+            .filter_map(|row| row.entry.ok())
+            .map(|entry| entry.external_id)
+            .collect::<Vec<_>>()
+    };
+    // importing the same statement again finds every row as a duplicate
+    assert_eq!(
+        ids(csv::parse(data, &mapping).unwrap()),
+        ids(csv::parse(data, &mapping).unwrap())
+    );
+}
+
+#[test]
+fn ofx_sgml_statement() {
+    let rows = ofx::parse(include_bytes!("fixtures/statement_sgml.ofx")).unwrap();
+    assert_eq!(rows.len(), 5);
+
+    let grocery = entry(&rows[0]);
+    assert_eq!(rows[0].line, 39);
+    assert_eq!(grocery.direction, TicketDirection::Out);
+    assert_eq!(grocery.amount, decimal("45.20"));
+    assert_eq!(grocery.currency.as_deref(), Some("USD"));
+    assert_eq!(grocery.created_at.to_rfc3339(), "2026-09-03T00:00:00+00:00");
+    assert_eq!(grocery.description, "GROCERY & MORE - POS PURCHASE");
+    assert_eq!(grocery.external_id, "ofx:12345678:2026090301");
+
+    let payroll = entry(&rows[1]);
+    assert_eq!(payroll.direction, TicketDirection::In);
+    assert_eq!(payroll.amount, decimal("2500.00"));
+    assert_eq!(payroll.description, "PAYROLL");
+
+    // the account of a transfer doesn't replace the one of the statement
+    let transfer = entry(&rows[2]);
+    assert_eq!(transfer.amount, decimal("100.50"));
+    assert_eq!(transfer.external_id, "ofx:12345678:2026092001");
+
+    assert_eq!(error(&rows[3]), "missing FITID");
+    assert_eq!(error(&rows[4]), "invalid date '2026'");
+}
+
+#[test]
+fn ofx_xml_statement() {
+    let rows = ofx::parse(include_bytes!("fixtures/statement_xml.qfx")).unwrap();
+    assert_eq!(rows.len(), 2);
+
+    let coffee = entry(&rows[0]);
+    assert_eq!(coffee.direction, TicketDirection::Out);
+    assert_eq!(coffee.amount, decimal("9.99"));
+    assert_eq!(coffee.currency.as_deref(), Some("EUR"));
+    assert_eq!(coffee.description, "Café Central");
+    assert_eq!(coffee.external_id, "ofx:4111222233334444:CC-0001");
+
+    let refund = entry(&rows[1]);
+    assert_eq!(refund.direction, TicketDirection::In);
+    assert_eq!(refund.amount, decimal("20.00"));
+    assert_eq!(refund.created_at.to_rfc3339(), "2026-09-10T00:00:00+00:00");
+    assert_eq!(refund.description, "Refund");
+}
+
+#[test]
+fn ofx_rejects_other_files() {
+    assert!(ofx::parse(include_bytes!("fixtures/statement.qif")).is_err());
+}
+
+#[test]
+fn qif_statement() {
+    let rows = qif::parse(include_bytes!("fixtures/statement.qif")).unwrap();
+    // the category list at the end isn't made of transactions
+    assert_eq!(rows.len(), 6);
+
+    let rent = entry(&rows[0]);
+    assert_eq!(rows[0].line, 2);
+    assert_eq!(rent.direction, TicketDirection::Out);
+    assert_eq!(rent.amount, decimal("1250.00"));
+    assert_eq!(rent.description, "Landlord - September rent");
+    assert_eq!(rent.category, None);
+    assert_eq!(rent.currency, None);
+
+    // identical rows are both kept, with different ids
+    let (first, second) = (entry(&rows[1]), entry(&rows[2]));
+    assert_eq!(first.created_at.to_rfc3339(), "2026-09-03T00:00:00+00:00");
+    assert_eq!(first.amount, second.amount);
+    assert_ne!(first.external_id, second.external_id);
+
+    let salary = entry(&rows[3]);
+    assert_eq!(salary.direction, TicketDirection::In);
+    assert_eq!(salary.amount, decimal("2500.00"));
+    assert_eq!(salary.created_at.to_rfc3339(), "2026-09-15T00:00:00+00:00");
+
+    assert_eq!(error(&rows[4]), "invalid date '13/45/2026'");
+    assert_eq!(error(&rows[5]), "missing amount");
+}
+
+#[test]
+fn qif_ids_are_stable() {
+    let first = qif::parse(include_bytes!("fixtures/statement.qif")).unwrap();
+    let second = qif::parse(include_bytes!("fixtures/statement.qif")).unwrap();
+    let ids = |rows: &[ParsedRow]| {
+        rows.iter()
+            .filter_map(|row| row.entry.as_ref().ok())
+            .map(|entry| entry.external_id.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ids(&first), ids(&second));
+}
+
+#[test]
+fn qif_decimal_comma_statement() {
+    let rows = qif::parse(include_bytes!("fixtures/statement_decimal_comma.qif")).unwrap();
+    let amounts: Vec<_> = rows.iter().map(|row| entry(row).amount).collect();
+    assert_eq!(
+        amounts,
+        [
+            decimal("1234.56"),
+            decimal("12.50"),
+            decimal("2500.00"),
+            decimal("1250")
+        ]
+    );
+    assert_eq!(entry(&rows[0]).direction, TicketDirection::Out);
+    assert_eq!(entry(&rows[2]).direction, TicketDirection::In);
+}
+
+#[test]
+fn qif_latin1_statement() {
+    let rows = qif::parse(include_bytes!("fixtures/statement_latin1.qif")).unwrap();
+    assert_eq!(entry(&rows[0]).description, "Épicerie");
+}
+
+#[test]
+fn camt053_statement() {
+    let rows = camt::parse(include_bytes!("fixtures/statement.camt053.xml")).unwrap();
+    assert_eq!(rows.len(), 4);
+
+    let rent = entry(&rows[0]);
+    assert_eq!(rows[0].line, 17);
+    assert_eq!(rent.direction, TicketDirection::Out);
+    assert_eq!(rent.amount, decimal("850.00"));
+    assert_eq!(rent.currency.as_deref(), Some("EUR"));
+    assert_eq!(rent.created_at.to_rfc3339(), "2026-09-02T00:00:00+00:00");
+    assert_eq!(
+        rent.description,
+        "Hausverwaltung Müller - Miete September Wohnung 3"
+    );
+    assert_eq!(
+        rent.external_id,
+        "camt:DE89370400440532013000:20260902-0001"
+    );
+
+    let salary = entry(&rows[1]);
+    assert_eq!(salary.direction, TicketDirection::In);
+    assert_eq!(salary.amount, decimal("3120.45"));
+    assert_eq!(salary.created_at.to_rfc3339(), "2026-09-25T00:00:00+00:00");
+    assert_eq!(salary.description, "ACME GmbH - SALARY-2026-09");
+
+    // without a reference the entry is told apart by its content
+    let fee = entry(&rows[2]);
+    assert_eq!(fee.description, "Kontofuehrungsgebuehr");
+    assert!(fee.external_id.starts_with("camt:"));
+    assert_eq!(
+        fee.external_id,
+        entry(&camt::parse(include_bytes!("fixtures/statement.camt053.xml")).unwrap()[2])
+            .external_id
+    );
+
+    assert_eq!(error(&rows[3]), "entry is not booked (PDNG)");
+}
+
+#[test]
+fn camt054_notification() {
+    let rows = camt::parse(include_bytes!("fixtures/notification.camt054.xml")).unwrap();
+    assert_eq!(rows.len(), 1);
+
+    let utilities = entry(&rows[0]);
+    assert_eq!(utilities.direction, TicketDirection::Out);
+    assert_eq!(utilities.amount, decimal("12.5"));
+    assert_eq!(utilities.description, "Stadtwerke");
+    assert_eq!(utilities.external_id, "camt:0532013000:ASR-77");
+}
+
+#[test]
+fn camt_rejects_other_files() {
+    assert!(camt::parse(include_bytes!("fixtures/statement_xml.qfx")).is_err());
+    assert!(camt::parse(b"<Document><Other/></Document>").is_err());
+}